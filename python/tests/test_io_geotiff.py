@@ -2,13 +2,14 @@
 Test I/O on GeoTIFF files.
 """
 
+import io
 import os
 import tempfile
 import urllib.request
 
 import numpy as np
 import pytest
-from cog3pio import CogReader, read_geotiff
+from cog3pio import CogReader, read_dataarray, read_geotiff
 
 
 # %%
@@ -60,6 +61,55 @@ def test_read_geotiff_multi_band():
     assert array.dtype == "float32"
 
 
+def test_read_geotiff_uint16_dtype():
+    """
+    Read a uint16 GeoTIFF file into an array that keeps the native uint16 dtype.
+    """
+    array = read_geotiff(
+        path="https://github.com/OSGeo/gdal/raw/v3.9.2/autotest/gcore/data/uint16.tif"
+    )
+    assert array.shape == (1, 20, 20)
+    assert array.dtype == "uint16"
+
+
+def test_read_geotiff_storage_options():
+    """
+    Read a GeoTIFF file from a remote URL, passing extra options to the storage backend.
+    """
+    array = read_geotiff(
+        path="https://github.com/pka/georaster/raw/v0.1.0/data/tiff/float32.tif",
+        storage_options={"timeout": "30s"},
+    )
+    assert array.shape == (1, 20, 20)
+
+
+def test_read_geotiff_mask_nodata():
+    """
+    Read a GeoTIFF file with NaN NoData values into a masked array.
+    """
+    array = read_geotiff(
+        path="https://github.com/cogeotiff/rio-tiler/raw/6.4.0/tests/fixtures/cog_nodata_nan.tif",
+        mask_nodata=True,
+    )
+    assert isinstance(array, np.ma.MaskedArray)
+    assert array.shape == (1, 549, 549)
+    np.testing.assert_equal(actual=array.mask, desired=np.isnan(array.data))
+
+
+def test_read_geotiff_timeout():
+    """
+    Check that a TimeoutError is raised when a remote file takes longer than the timeout
+    to fetch.
+    """
+    with pytest.raises(
+        TimeoutError, match="Timed out after 0.000001s while fetching"
+    ):
+        read_geotiff(
+            path="https://github.com/pka/georaster/raw/v0.1.0/data/tiff/float32.tif",
+            timeout=1e-6,
+        )
+
+
 def test_read_geotiff_invalid_filepath():
     """
     Check that a ValueError is raised when an invalid filepath is passed to read_geotiff.
@@ -108,9 +158,7 @@ def test_read_geotiff_unsupported_dtype():
     is passed to read_geotiff.
     """
     with pytest.raises(
-        ValueError,
-        match="The Decoder does not support the image format "
-        r"`Sample format \[Unknown\(5\)\] is unsupported",
+        ValueError, match=r"Complex sample format \(CInt16\) is not supported"
     ):
         read_geotiff(
             path="https://github.com/corteva/rioxarray/raw/0.15.1/test/test_data/input/cint16.tif"
@@ -132,3 +180,113 @@ def test_CogReader_as_numpy():
             [[[1.41, 1.23, 0.78], [0.32, -0.23, -1.88]]], dtype=np.float32
         ),
     )
+
+
+def test_CogReader_array_protocol():
+    """
+    Ensure that the CogReader class can be converted to a numpy.ndarray via the array
+    protocol, optionally casting to another dtype.
+    """
+    reader = CogReader(
+        path="https://github.com/rasterio/rasterio/raw/1.3.9/tests/data/float32.tif"
+    )
+    array = np.asarray(reader)
+    assert array.dtype == np.float32
+    assert array.shape == (1, 2, 3)  # band, height, width
+    np.testing.assert_equal(actual=array, desired=reader.as_numpy())
+
+    array = np.asarray(reader, dtype=np.float64)
+    assert array.dtype == np.float64
+    assert array[0, 1, 2] == np.float64(np.float32(-1.88))
+
+
+def test_CogReader_read_overview():
+    """
+    Ensure that the CogReader class can decode the reduced-resolution overviews of a
+    Cloud-optimized GeoTIFF.
+    """
+    reader = CogReader(
+        path="https://github.com/cogeotiff/rio-tiler/raw/6.4.0/tests/fixtures/cog_nodata_nan.tif"
+    )
+    assert reader.num_overviews > 0
+    array = reader.read_overview(level=0)
+    assert array.dtype == np.float32
+    assert array.shape[0] == 1  # band
+    assert array.shape[1] < 549 and array.shape[2] < 549  # height, width
+
+    with pytest.raises(ValueError, match="Overview level 99 does not exist"):
+        reader.read_overview(level=99)
+
+
+def test_CogReader_context_manager(geotiff_path):
+    """
+    Ensure that the CogReader class can be used as a context manager, and that reading
+    from it after it is closed raises a ValueError.
+    """
+    with CogReader(path=geotiff_path) as reader:
+        array = reader.as_numpy()
+        assert array.shape == (1, 20, 20)
+
+    with pytest.raises(ValueError, match="I/O operation on closed CogReader"):
+        reader.as_numpy()
+
+
+def test_CogReader_from_fileobj(geotiff_path):
+    """
+    Ensure that the CogReader class can decode from Python file-like objects.
+    """
+    with open(geotiff_path, mode="rb") as file:
+        array = CogReader.from_fileobj(file).as_numpy()
+        assert array.shape == (1, 20, 20)
+
+        file.seek(0)
+        buffer = io.BytesIO(file.read())
+        np.testing.assert_equal(
+            actual=CogReader.from_fileobj(buffer).as_numpy(), desired=array
+        )
+
+
+def test_read_dataarray():
+    """
+    Read a GeoTIFF file into a dict of the data array, its coordinates, transform and
+    coordinate reference system.
+    """
+    result = read_dataarray(
+        path="https://github.com/cogeotiff/rio-tiler/raw/6.4.0/tests/fixtures/cog_nodata_nan.tif"
+    )
+    assert set(result) == {"data", "x", "y", "transform", "crs"}
+    assert result["data"].shape == (1, 549, 549)
+    assert result["data"].dtype == np.float32
+    assert result["x"].shape == (549,)
+    assert result["y"].shape == (549,)
+    assert result["x"][0] == 500080.0  # center of upper-left pixel
+    assert result["y"][0] == 5299940.0
+    assert result["transform"] == (200.0, 0.0, 499980.0, 0.0, -200.0, 5300040.0)
+    assert result["crs"].startswith("EPSG:")
+
+
+def test_CogReader_transform():
+    """
+    Ensure that the CogReader class's `transform` method returns the affine
+    transformation coefficients as a tuple of 6 floats.
+    """
+    reader = CogReader(
+        path="https://github.com/cogeotiff/rio-tiler/raw/6.4.0/tests/fixtures/cog_nodata_nan.tif"
+    )
+    assert reader.transform() == (200.0, 0.0, 499980.0, 0.0, -200.0, 5300040.0)
+
+
+def test_CogReader_pixel_to_world_to_pixel():
+    """
+    Ensure that the CogReader class's `pixel_to_world` and `world_to_pixel` methods
+    convert between image pixel and geographic/projected coordinates.
+    """
+    reader = CogReader(
+        path="https://github.com/cogeotiff/rio-tiler/raw/6.4.0/tests/fixtures/cog_nodata_nan.tif"
+    )
+    x, y = reader.pixel_to_world(row=1.0, col=2.0)
+    assert (x, y) == (500380.0, 5299840.0)
+
+    row, col = reader.world_to_pixel(x=x, y=y)
+    assert row == pytest.approx(1.0)
+    assert col == pytest.approx(2.0)
@@ -1,292 +1,6053 @@
-use std::io::{Read, Seek};
+use std::collections::HashMap;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::ops::{Deref, DerefMut, Range};
+#[cfg(feature = "arrow")]
+use std::sync::Arc;
+use std::sync::Mutex;
 
-use geo::AffineTransform;
-use ndarray::{Array, Array1, Array3};
-use num_traits::FromPrimitive;
-use tiff::decoder::{Decoder, DecodingResult, Limits};
-use tiff::tags::Tag;
-use tiff::{ColorType, TiffError, TiffFormatError, TiffResult, TiffUnsupportedError};
+#[cfg(feature = "arrow")]
+use arrow_array::{
+    ArrayRef, Float32Array, Float64Array, Int16Array, Int32Array, Int64Array, Int8Array,
+    UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+};
+use bytes::Bytes;
+use geo::{AffineTransform, Coord};
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+use ndarray::{
+    s, Array, Array1, Array2, Array3, Array4, ArrayView3, ArrayViewMut3, Axis, Order, ShapeBuilder,
+};
+use num_traits::{AsPrimitive, FromPrimitive, ToBytes, ToPrimitive};
+use object_store::path::Path;
+use object_store::{GetResult, ObjectStore};
+#[cfg(feature = "parallel")]
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use tiff::decoder::{ChunkType, Decoder, DecodingResult, Limits};
+use tiff::tags::{CompressionMethod, PlanarConfiguration, Predictor, SampleFormat, Tag};
+use tiff::{ColorType, TiffError, TiffFormatError, TiffResult, TiffUnsupportedError, UsageError};
+
+/// NewSubfileType bit flag for a reduced-resolution version of another image (overview)
+const SUBFILE_REDUCED_IMAGE: u32 = 1;
+/// NewSubfileType bit flag for a transparency mask of another image
+const SUBFILE_MASK: u32 = 4;
+/// GeoKey ID of the GTModelTypeGeoKey, i.e. the type of coordinate reference system
+const GT_MODEL_TYPE_GEO_KEY: u16 = 1024;
+/// GeoKey IDs of the GTCitationGeoKey, PCSCitationGeoKey and GeogCitationGeoKey, i.e. the
+/// descriptions of the overall, projected and geographic coordinate reference systems
+const CITATION_GEO_KEYS: [u16; 3] = [1026, 3073, 2049];
+/// GTModelTypeGeoKey value for a geographic (latitude/longitude) coordinate system
+const MODEL_TYPE_GEOGRAPHIC: u16 = 2;
+/// GeoKey IDs of the ProjectedCSTypeGeoKey and GeographicTypeGeoKey, i.e. the EPSG codes
+/// of the projected and geographic coordinate reference systems
+const CRS_TYPE_GEO_KEYS: [u16; 2] = [3072, 2048];
+/// GeoKey value for a user-defined coordinate reference system, which has no EPSG code
+const USER_DEFINED: u16 = 32767;
 
 /// Cloud-optimized GeoTIFF reader
-pub(crate) struct CogReader<R: Read + Seek> {
+pub struct CogReader<R: Read + Seek> {
     /// TIFF decoder
-    pub decoder: Decoder<R>,
+    decoder: Decoder<R>,
+    /// Index of the Image File Directory (IFD) that the decoder currently reads from
+    ifd_index: usize,
+    /// Whether the file is a BigTIFF (version 43) rather than a classic TIFF (version 42)
+    is_bigtiff: bool,
+    /// Byte order of the file, from the byte order mark in the TIFF header
+    byte_order: ByteOrder,
+    /// Byte counts of every chunk (tile or strip) in the current IFD, read on first use
+    chunk_byte_counts: Option<Vec<u64>>,
+    /// Options applied when decoding the image with [`ndarray`](Self::ndarray)
+    options: ReadOptions,
+    /// GDAL structural metadata from the ghost area after the TIFF header, if present
+    ghost_header: Option<GhostHeader>,
+    /// Georeferencing metadata parsed from the tags of the current IFD, read on first use
+    metadata: MetadataCache,
+}
+
+/// Image and georeferencing metadata of an Image File Directory (IFD), cached so that
+/// repeated calls to accessors such as [`CogReader::transform`] do not re-read the tags from
+/// the stream. Fields are `None` until first read, and are cleared when another IFD is
+/// selected.
+#[derive(Clone, Debug, Default)]
+struct MetadataCache {
+    /// Width and height of the image
+    dimensions: Option<(u32, u32)>,
+    /// Color type from the PhotometricInterpretation, SamplesPerPixel and BitsPerSample tags
+    color_type: Option<ColorType>,
+    /// Number of samples (bands) per pixel
+    num_samples: Option<usize>,
+    /// Values of the ModelPixelScaleTag
+    pixel_scale: Option<Option<Vec<f64>>>,
+    /// Values of the ModelTiepointTag
+    tiepoint: Option<Option<Vec<f64>>>,
+    /// Affine transform from the ModelPixelScale/ModelTiepoint/ModelTransformation tags
+    transform: Option<AffineTransform<f64>>,
+    /// NoData value from the GDAL_NODATA tag
+    nodata: Option<Option<f64>>,
+    /// EPSG code from the GeoKeyDirectoryTag
+    epsg_code: Option<Option<u32>>,
+}
+
+/// Options applied when decoding an image with [`CogReader::ndarray`], as configured by a
+/// [`CogReaderBuilder`]
+#[derive(Clone, Debug)]
+struct ReadOptions {
+    /// Indices of the bands to return, or `None` for all bands
+    bands: Option<Vec<usize>>,
+    /// Whether to apply the per-band scale and offset from the GDAL metadata
+    apply_scale_offset: bool,
+    /// Memory order of the returned array
+    order: Order,
+    /// Whether to return an error for images with lossy compression
+    require_lossless: bool,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self {
+            bands: None,
+            apply_scale_offset: false,
+            order: Order::RowMajor,
+            require_lossless: false,
+        }
+    }
+}
+
+/// Builder for a [`CogReader`] with non-default options, for configuring the reader with
+/// chained method calls before opening a stream with [`build`](Self::build).
+///
+/// Options that affect the decoded pixel data (bands, scale and offset, memory order) are
+/// applied by [`CogReader::ndarray`], as well as by the methods built on it (e.g.
+/// [`CogReader::read_overview`]) and by [`CogReader::read_resampled`] and
+/// [`CogReader::read_preview`], but not by windowed or tiled reads.
+/// [`CogReader::read_image_into`] returns an error for them instead.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::fs::File;
+///
+/// use cog3pio::io::geotiff::CogReaderBuilder;
+/// use ndarray::Order;
+///
+/// let file = File::open("image.tif").unwrap();
+/// let mut reader = CogReaderBuilder::new()
+///     .bands(vec![2, 1, 0])
+///     .apply_scale_offset(true)
+///     .order(Order::ColumnMajor)
+///     .build(file)
+///     .unwrap();
+/// let array = reader.ndarray::<f32>().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct CogReaderBuilder {
+    /// Limits on the memory used by the TIFF decoder
+    limits: Limits,
+    /// Options applied when decoding the image
+    options: ReadOptions,
+}
+
+impl Default for CogReaderBuilder {
+    fn default() -> Self {
+        Self {
+            limits: Limits::unlimited(),
+            options: ReadOptions::default(),
+        }
+    }
+}
+
+impl CogReaderBuilder {
+    /// Create a new builder with the default options, which are the same as those used by
+    /// [`CogReader::new`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits on the memory used by the TIFF decoder, e.g. to guard against decompression
+    /// bombs. Default is [`Limits::unlimited`].
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Indices of the bands to decode, in the order that they are returned. Indices can be
+    /// repeated, and indices beyond the number of bands cause an error when decoding.
+    /// Default is all bands.
+    pub fn bands(mut self, bands: Vec<usize>) -> Self {
+        self.options.bands = Some(bands);
+        self
+    }
+
+    /// Whether to convert the decoded values to physical values using the per-band
+    /// [scale and offset](CogReader::scale_offset) from the GDAL metadata, which is done
+    /// in f64 before converting to the requested dtype. Default is `false`.
+    ///
+    /// Fractional values are truncated towards zero when converting to an integer dtype,
+    /// and decoding fails if a scaled value (e.g. NaN, or a negative value for an unsigned
+    /// dtype) cannot be represented in the requested dtype. Use a float dtype, or
+    /// [`read_geotiff_as`] to saturate at the bounds of the dtype instead.
+    pub fn apply_scale_offset(mut self, apply_scale_offset: bool) -> Self {
+        self.options.apply_scale_offset = apply_scale_offset;
+        self
+    }
+
+    /// Memory order of the decoded array, either C ([`Order::RowMajor`]) or Fortran
+    /// ([`Order::ColumnMajor`]) contiguous. Default is [`Order::RowMajor`].
+    pub fn order(mut self, order: Order) -> Self {
+        self.options.order = order;
+        self
+    }
+
+    /// Whether exact values are required, so that decoding returns an error for images
+    /// with [lossy compression](CogReader::is_lossy_compression) (e.g. JPEG) instead of
+    /// silently returning approximate values. Default is `false`.
+    pub fn require_lossless(mut self, require_lossless: bool) -> Self {
+        self.options.require_lossless = require_lossless;
+        self
+    }
+
+    /// Create a new GeoTIFF decoder with the configured options that decodes from a stream
+    /// buffer
+    pub fn build<R: Read + Seek>(self, stream: R) -> TiffResult<CogReader<R>> {
+        CogReader::with_options(stream, self.limits, self.options)
+    }
 }
 
 impl<R: Read + Seek> CogReader<R> {
     /// Create a new GeoTIFF decoder that decodes from a stream buffer
+    ///
+    /// Use a [`CogReaderBuilder`] to create a decoder with non-default options.
     pub fn new(stream: R) -> TiffResult<Self> {
-        // Open TIFF stream with decoder
-        let mut decoder = Decoder::new(stream)?;
-        decoder = decoder.with_limits(Limits::unlimited());
-
-        Ok(Self { decoder })
+        Self::with_options(stream, Limits::unlimited(), ReadOptions::default())
     }
 
-    /// Decode GeoTIFF image to an [`ndarray::Array`]
-    pub fn ndarray<T: FromPrimitive + 'static>(&mut self) -> TiffResult<Array3<T>> {
-        // Count number of bands
-        let color_type = self.decoder.colortype()?;
-        let num_bands: usize = match color_type {
-            ColorType::Multiband {
-                bit_depth: _,
-                num_samples,
-            } => num_samples as usize,
-            ColorType::Gray(_) => 1,
-            _ => {
-                return Err(TiffError::UnsupportedError(
-                    TiffUnsupportedError::UnsupportedColorType(color_type),
-                ))
-            }
+    /// Create a new GeoTIFF decoder with the given decoder limits and read options
+    fn with_options(mut stream: R, limits: Limits, options: ReadOptions) -> TiffResult<Self> {
+        // Read byte order mark and version number from TIFF header
+        let mut header = [0u8; 4];
+        stream.read_exact(&mut header)?;
+        stream.seek(SeekFrom::Start(0))?;
+        let byte_order: ByteOrder = match &header[0..2] {
+            b"MM" => ByteOrder::BigEndian,
+            _ => ByteOrder::LittleEndian,
         };
-
-        // Get image dimensions
-        let (width, height): (u32, u32) = self.decoder.dimensions()?;
-
-        // Get image pixel data
-        let decode_result = self.decoder.read_image()?;
-        let image_data: Vec<T> = match decode_result {
-            DecodingResult::U8(img_data) => {
-                img_data.iter().map(|v| T::from_u8(*v).unwrap()).collect()
-            }
-            DecodingResult::U16(img_data) => {
-                img_data.iter().map(|v| T::from_u16(*v).unwrap()).collect()
-            }
-            DecodingResult::U32(img_data) => {
-                img_data.iter().map(|v| T::from_u32(*v).unwrap()).collect()
-            }
-            DecodingResult::U64(img_data) => {
-                img_data.iter().map(|v| T::from_u64(*v).unwrap()).collect()
-            }
-            DecodingResult::I8(img_data) => {
-                img_data.iter().map(|v| T::from_i8(*v).unwrap()).collect()
-            }
-            DecodingResult::I16(img_data) => {
-                img_data.iter().map(|v| T::from_i16(*v).unwrap()).collect()
-            }
-            DecodingResult::I32(img_data) => {
-                img_data.iter().map(|v| T::from_i32(*v).unwrap()).collect()
-            }
-            DecodingResult::I64(img_data) => {
-                img_data.iter().map(|v| T::from_i64(*v).unwrap()).collect()
-            }
-            DecodingResult::F32(img_data) => {
-                img_data.iter().map(|v| T::from_f32(*v).unwrap()).collect()
-            }
-            DecodingResult::F64(img_data) => {
-                img_data.iter().map(|v| T::from_f64(*v).unwrap()).collect()
-            }
+        let version: u16 = match byte_order {
+            ByteOrder::BigEndian => u16::from_be_bytes([header[2], header[3]]),
+            ByteOrder::LittleEndian => u16::from_le_bytes([header[2], header[3]]),
         };
 
-        // Put image pixel data into an ndarray
-        let array_data: Array3<T> =
-            Array3::from_shape_vec((num_bands, height as usize, width as usize), image_data)
-                .map_err(|_| TiffFormatError::InconsistentSizesEncountered)?;
+        // Read GDAL structural metadata directly after the 8 (or 16 for BigTIFF) byte header
+        let ghost_header: Option<GhostHeader> =
+            read_ghost_header(&mut stream, if version == 43 { 16 } else { 8 })?;
+        stream.seek(SeekFrom::Start(0))?;
 
-        Ok(array_data)
+        // Open TIFF stream with decoder
+        let mut decoder = Decoder::new(stream)?;
+        decoder = decoder.with_limits(limits);
+
+        Ok(Self {
+            decoder,
+            ifd_index: 0,
+            is_bigtiff: version == 43,
+            byte_order,
+            chunk_byte_counts: None,
+            options,
+            ghost_header,
+            metadata: MetadataCache::default(),
+        })
     }
 
-    /// Affine transformation for 2D matrix extracted from TIFF tag metadata, used to transform
-    /// image pixel (row, col) coordinates to and from geographic/projected (x, y) coordinates.
+    /// Mutable access to the underlying [`tiff`] [`Decoder`], as an escape hatch for reading
+    /// things that `CogReader` does not wrap, e.g. custom or exotic TIFF tags.
     ///
-    /// ```text
-    /// | x' |   | a b c | | x |
-    /// | y' | = | d e f | | y |
-    /// | 1  |   | 0 0 1 | | 1 |
-    /// ```
+    /// The decoder's API is that of the [`tiff`] crate, which may change between versions
+    /// independently of cog3pio, so code using this is not covered by cog3pio's semver
+    /// guarantees. Seeking the decoder to another image (e.g. with
+    /// [`Decoder::seek_to_image`]) is not tracked by the `CogReader`, so use
+    /// [`select_image`](Self::select_image) for that instead.
+    pub fn decoder_mut(&mut self) -> &mut Decoder<R> {
+        &mut self.decoder
+    }
+
+    /// Whether the file uses the BigTIFF format (for files larger than 4GB), as opposed to
+    /// the classic TIFF format, determined from the version number in the TIFF header.
+    pub fn is_bigtiff(&self) -> bool {
+        self.is_bigtiff
+    }
+
+    /// Byte order (endianness) in which the file's headers and pixel data are stored,
+    /// determined from the byte order mark ("II" or "MM") in the TIFF header.
+    pub fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    /// GDAL structural metadata (the "ghost area") stored directly after the TIFF header of
+    /// COGs written by GDAL, describing the layout of the file, e.g. whether all IFDs are
+    /// stored before the tile data, and whether mask tiles are interleaved with the imagery
+    /// tiles. Returns `None` for files not written by GDAL's COG driver.
     ///
-    /// where (`x'` and `y'`) are world coordinates, and (`x`, `y`) are the pixel's
-    /// image coordinates. Letters a to f represent:
+    /// References:
+    /// - <https://gdal.org/drivers/raster/cog.html#header-ghost-area>
+    pub fn ghost_header(&mut self) -> TiffResult<Option<GhostHeader>> {
+        Ok(self.ghost_header.clone())
+    }
+
+    /// Number of full-resolution images in the TIFF file.
     ///
-    /// - `a` - width of a pixel (x-resolution)
-    /// - `b` - row rotation (typically zero)
-    /// - `c` - x-coordinate of the *center* of the upper-left pixel (x-origin)
-    /// - `d` - column rotation (typically zero)
-    /// - `e` - height of a pixel (y-resolution, typically negative)
-    /// - `f` - y-coordinate of the *center* of the upper-left pixel (y-origin)
+    /// Multi-page TIFF files can contain several independent images, each stored in a
+    /// separate Image File Directory (IFD). Reduced-resolution overviews and transparency
+    /// masks (as indicated by the NewSubfileType tag) are not counted.
+    pub fn num_images(&mut self) -> TiffResult<usize> {
+        let num_images: usize = self
+            .subfile_types()?
+            .iter()
+            .filter(|subfile_type| is_full_image(**subfile_type))
+            .count();
+
+        Ok(num_images)
+    }
+
+    /// Select the full-resolution image (by its zero-based index, see
+    /// [`num_images`](Self::num_images)) that subsequent reads will decode from.
     ///
-    /// References:
-    /// - <https://docs.ogc.org/is/19-008r4/19-008r4.html#_coordinate_transformations>
-    fn transform(&mut self) -> TiffResult<AffineTransform<f64>> {
-        // Get x and y axis rotation (not yet implemented)
-        let (x_rotation, y_rotation): (f64, f64) =
-            match self.decoder.get_tag_f64_vec(Tag::ModelTransformationTag) {
-                Ok(_model_transformation) => unimplemented!("Non-zero rotation is not handled yet"),
-                Err(_) => (0.0, 0.0),
-            };
+    /// Metadata cached from the previously selected image (e.g. its dimensions and
+    /// transform) is cleared, so that it is read again from the newly selected one.
+    pub fn select_image(&mut self, index: usize) -> TiffResult<()> {
+        let ifd_index: usize = self
+            .subfile_types()?
+            .iter()
+            .enumerate()
+            .filter(|(_, subfile_type)| is_full_image(**subfile_type))
+            .nth(index)
+            .map(|(ifd_index, _)| ifd_index)
+            .ok_or(TiffError::FormatError(
+                TiffFormatError::ImageFileDirectoryNotFound,
+            ))?;
+        self.seek_to_ifd(ifd_index)
+    }
 
-        // Get pixel size in x and y direction
-        let pixel_scale: Vec<f64> = self.decoder.get_tag_f64_vec(Tag::ModelPixelScaleTag)?;
-        let [x_scale, y_scale, _z_scale] = pixel_scale[0..3] else {
-            return Err(TiffError::FormatError(TiffFormatError::InvalidTag));
-        };
+    /// NewSubfileType (tag 254) bit flags of every Image File Directory (IFD) in the file
+    fn subfile_types(&mut self) -> TiffResult<Vec<u32>> {
+        let mut subfile_types: Vec<u32> = vec![];
 
-        // Get x and y coordinates of upper left pixel
-        let tie_points: Vec<f64> = self.decoder.get_tag_f64_vec(Tag::ModelTiepointTag)?;
-        let [_i, _j, _k, x_origin, y_origin, _z_origin] = tie_points[0..6] else {
-            return Err(TiffError::FormatError(TiffFormatError::InvalidTag));
-        };
+        // Seek to each IFD by index, as the decoder's `more_images` flag is not updated when
+        // seeking back to an earlier IFD, so it can't be relied on for a second pass
+        loop {
+            match self.decoder.seek_to_image(subfile_types.len()) {
+                Ok(()) => {}
+                Err(TiffError::FormatError(TiffFormatError::ImageFileDirectoryNotFound)) => break,
+                Err(err) => return Err(err),
+            }
+            let subfile_type: u32 = self
+                .decoder
+                .find_tag_unsigned(Tag::NewSubfileType)?
+                .unwrap_or(0);
+            subfile_types.push(subfile_type);
+        }
 
-        // Create affine transformation matrix
-        let transform = AffineTransform::new(
-            x_scale, x_rotation, x_origin, y_rotation, -y_scale, y_origin,
-        );
+        // Go back to the previously selected IFD
+        self.decoder.seek_to_image(self.ifd_index)?;
 
-        Ok(transform)
+        Ok(subfile_types)
     }
 
-    /// Get list of x and y coordinates
-    pub fn xy_coords(&mut self) -> TiffResult<(Array1<f64>, Array1<f64>)> {
-        let transform = self.transform()?; // affine transformation matrix
+    /// Point the decoder at the Image File Directory (IFD) with the given index
+    fn seek_to_ifd(&mut self, ifd_index: usize) -> TiffResult<()> {
+        self.decoder.seek_to_image(ifd_index)?;
+        self.ifd_index = ifd_index;
+        self.chunk_byte_counts = None;
+        self.metadata = MetadataCache::default();
 
-        // Get spatial resolution in x and y dimensions
-        let x_res: &f64 = &transform.a();
-        let y_res: &f64 = &transform.e();
+        Ok(())
+    }
 
-        // Get xy coordinate of the center of the top left pixel
-        let x_origin: &f64 = &(transform.xoff() + x_res / 2.0);
-        let y_origin: &f64 = &(transform.yoff() + y_res / 2.0);
+    /// Decode GeoTIFF image to an [`ndarray::Array`]
+    ///
+    /// The array is flipped and/or transposed according to the
+    /// [`orientation`](Self::orientation) of the image, so that the first row and column
+    /// are always at the top-left. Any band selection, scale and offset, or memory order
+    /// configured with a [`CogReaderBuilder`] is then applied.
+    pub fn ndarray<T: FromPrimitive + Clone + 'static>(&mut self) -> TiffResult<Array3<T>> {
+        self.check_lossless()?;
+        let scale_offset: Option<Vec<(f64, f64)>> = match self.options.apply_scale_offset {
+            true => Some(self.scale_offset()?),
+            false => None,
+        };
+        let array_data: Array3<T> = self.decode_with_options(scale_offset.as_deref())?;
 
-        // Get number of pixels along the x and y dimensions
-        let (x_pixels, y_pixels): (u32, u32) = self.decoder.dimensions()?;
+        if self.options.order == Order::ColumnMajor {
+            return to_fortran_order(array_data);
+        }
 
-        // Get xy coordinate of the center of the bottom right pixel
-        let x_end: f64 = x_origin + x_res * x_pixels as f64;
-        let y_end: f64 = y_origin + y_res * y_pixels as f64;
+        Ok(array_data)
+    }
 
-        // Get array of x-coordinates and y-coordinates
-        let x_coords = Array::range(x_origin.to_owned(), x_end, x_res.to_owned());
-        let y_coords = Array::range(y_origin.to_owned(), y_end, y_res.to_owned());
+    /// Decode GeoTIFF image to an [`ndarray::Array`] in the orientation of the visual image,
+    /// applying the given per-band scale and offset (if any) and the band selection of the
+    /// read options, but not their memory order
+    fn decode_with_options<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        scale_offset: Option<&[(f64, f64)]>,
+    ) -> TiffResult<Array3<T>> {
+        let array_data: Array3<T> = match scale_offset {
+            Some(scale_offset) => {
+                let mut array_data: Array3<f64> = self.decode_ndarray()?;
+                for (mut band, (scale, offset)) in array_data.outer_iter_mut().zip(scale_offset) {
+                    band.mapv_inplace(|value| value * scale + offset);
+                }
+                scaled_to_dtype(array_data)?
+            }
+            None => self.decode_ndarray()?,
+        };
 
-        Ok((x_coords, y_coords))
+        // Select bands
+        let array_data: Array3<T> = match &self.options.bands {
+            Some(bands) => {
+                let num_bands: usize = array_data.len_of(Axis(0));
+                if let Some(band) = bands.iter().find(|band| **band >= num_bands) {
+                    return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                        "Band index {band} is out of range for image with {num_bands} bands"
+                    ))));
+                }
+                array_data.select(Axis(0), bands)
+            }
+            None => array_data,
+        };
+
+        Ok(array_data)
     }
-}
 
-/// Synchronously read a GeoTIFF file into an [`ndarray::Array`]
-pub fn read_geotiff<T: FromPrimitive + 'static, R: Read + Seek>(
-    stream: R,
-) -> TiffResult<Array3<T>> {
-    // Open TIFF stream with decoder
-    let mut reader = CogReader::new(stream)?;
+    /// Return an error for images with lossy compression if exact values were requested with
+    /// [`CogReaderBuilder::require_lossless`]
+    fn check_lossless(&mut self) -> TiffResult<()> {
+        if self.options.require_lossless && self.is_lossy_compression()? {
+            let code: u16 = self.compression()?.to_u16();
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "Image is compressed with lossy {} compression (method {code}), but exact \
+                 values were requested",
+                compression_name(code)
+            ))));
+        }
 
-    // Decode TIFF into ndarray
-    let array_data: Array3<T> = reader.ndarray()?;
+        Ok(())
+    }
 
-    Ok(array_data)
-}
+    /// Decode GeoTIFF image to an [`ndarray::Array`] in the orientation of the visual image,
+    /// without applying any read options
+    fn decode_ndarray<T: FromPrimitive + Clone + 'static>(&mut self) -> TiffResult<Array3<T>> {
+        let orientation: u16 = self.orientation()?;
 
-#[cfg(test)]
-mod tests {
-    use std::io::{Cursor, Seek, SeekFrom};
+        // Count number of bands
+        let num_bands: usize = self.num_samples()?;
 
-    use geo::AffineTransform;
-    use ndarray::{array, s};
-    use object_store::parse_url;
-    use tempfile::tempfile;
-    use tiff::encoder::{colortype, TiffEncoder};
-    use url::Url;
+        // Decode sparse images chunk by chunk, filling in chunks that are not stored
+        if self.has_sparse_chunks()? {
+            let (width, height): (u32, u32) = self.oriented_dimensions()?;
+            return self.read_window(0, 0, width, height);
+        }
+
+        // Get image dimensions
+        let (width, height): (u32, u32) = self.dimensions()?;
 
-    use crate::io::geotiff::{read_geotiff, CogReader};
+        // Get image pixel data
+        self.check_compression()?;
+        self.check_sample_format()?;
+        let decode_result = self.decoder.read_image().map_err(truncation_error)?;
+        let image_data: Vec<T> = decoding_result_to_vec(decode_result);
 
-    #[test]
-    fn test_read_geotiff() {
-        // Generate some data
-        let mut image_data = Vec::new();
-        for y in 0..10 {
-            for x in 0..20 {
-                let val = y + x;
-                image_data.push(val as f32);
-            }
+        // Check that the number of decoded samples matches the image dimensions
+        let expected_len: usize = num_bands * height as usize * width as usize;
+        if image_data.len() != expected_len {
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "Decoded {} samples, expected {expected_len} (bands={num_bands} * \
+                 height={height} * width={width}), the file may be truncated or corrupt",
+                image_data.len()
+            ))));
         }
 
-        // Write a BigTIFF file
-        let mut file = tempfile().unwrap();
-        let mut bigtiff = TiffEncoder::new_big(&mut file).unwrap();
-        bigtiff
-            .write_image::<colortype::Gray32Float>(20, 10, &image_data) // width, height, data
-            .unwrap();
-        file.seek(SeekFrom::Start(0)).unwrap();
+        // Put image pixel data into an ndarray. Bands are interleaved pixel by pixel (chunky),
+        // so move the band axis first, unless they are stored in separate planes
+        let is_planar: bool = self
+            .decoder
+            .find_tag_unsigned::<u16>(Tag::PlanarConfiguration)?
+            == Some(2);
+        let array_data: Array3<T> = if is_planar || num_bands == 1 {
+            Array3::from_shape_vec((num_bands, height as usize, width as usize), image_data)
+                .map_err(|_| TiffFormatError::InconsistentSizesEncountered)?
+        } else {
+            Array3::from_shape_vec((height as usize, width as usize, num_bands), image_data)
+                .map_err(|_| TiffFormatError::InconsistentSizesEncountered)?
+                .permuted_axes([2, 0, 1])
+        };
 
-        // Read a BigTIFF file
-        let arr = read_geotiff(file).unwrap();
-        assert_eq!(arr.ndim(), 3);
-        assert_eq!(arr.dim(), (1, 10, 20)); // (channels, height, width)
-        let first_band = arr.slice(s![0, .., ..]);
-        assert_eq!(first_band.nrows(), 10); // y-axis
-        assert_eq!(first_band.ncols(), 20); // x-axis
-        assert_eq!(arr.mean(), Some(14.0));
+        Ok(apply_orientation(array_data, orientation))
     }
 
-    #[tokio::test]
-    async fn test_read_geotiff_multi_band() {
-        let cog_url: &str =
-            "https://github.com/locationtech/geotrellis/raw/v3.7.1/raster/data/one-month-tiles-multiband/result.tif";
-        let tif_url = Url::parse(cog_url).unwrap();
-        let (store, location) = parse_url(&tif_url).unwrap();
-
-        let result = store.get(&location).await.unwrap();
-        let bytes = result.bytes().await.unwrap();
-        let stream = Cursor::new(bytes);
+    /// Decode GeoTIFF image to an [`ndarray::Array`] of shape (band, height, width), along
+    /// with a validity mask of shape (height, width).
+    ///
+    /// Pixels in the mask are `false` (invalid) where every band is equal to the
+    /// [`nodata`](Self::nodata) value or is NaN, and `true` (valid) otherwise.
+    pub fn read_with_mask<T: FromPrimitive + ToPrimitive + Clone + 'static>(
+        &mut self,
+    ) -> TiffResult<(Array3<T>, Array2<bool>)> {
+        let nodata: Option<f64> = self.nodata()?;
+        let array_data: Array3<T> = self.ndarray()?;
 
-        let mut reader = CogReader::new(stream).unwrap();
-        let array = reader.ndarray().unwrap();
+        let mask: Array2<bool> = array_data.map_axis(Axis(0), |pixel| {
+            pixel.iter().any(|value| match value.to_f64() {
+                Some(value) => !value.is_nan() && Some(value) != nodata,
+                None => true,
+            })
+        });
 
-        assert_eq!(array.dim(), (2, 512, 512));
-        assert_eq!(array.mean(), Some(225.17439122416545));
+        Ok((array_data, mask))
     }
 
-    #[tokio::test]
-    async fn test_read_geotiff_uint16_dtype() {
-        let cog_url: &str =
-            "https://github.com/OSGeo/gdal/raw/v3.9.2/autotest/gcore/data/uint16.tif";
-        let tif_url = Url::parse(cog_url).unwrap();
-        let (store, location) = parse_url(&tif_url).unwrap();
+    /// Decode a single-band GeoTIFF image to a 2D [`ndarray::Array`] of shape
+    /// (height, width). Returns an error if the image has more than one band, use
+    /// [`ndarray`](Self::ndarray) for multi-band images instead.
+    pub fn read_image_2d<T: FromPrimitive + Clone + 'static>(&mut self) -> TiffResult<Array2<T>> {
+        let num_bands: usize = self.num_samples()?;
+        if num_bands != 1 {
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "Cannot read image with {num_bands} bands as a 2D array"
+            ))));
+        }
 
-        let result = store.get(&location).await.unwrap();
-        let bytes = result.bytes().await.unwrap();
-        let stream = Cursor::new(bytes);
+        let array_data: Array3<T> = self.ndarray()?;
 
-        let mut reader = CogReader::new(stream).unwrap();
-        let array = reader.ndarray::<u16>().unwrap();
+        Ok(array_data.index_axis_move(Axis(0), 0))
+    }
 
-        assert_eq!(array.dim(), (1, 20, 20));
-        assert_eq!(array.mean(), Some(126));
+    /// Size in bytes of the decoded image pixel data, i.e. bands * height * width * bytes
+    /// per sample, computed from the TIFF tags without decoding the image. This can be used
+    /// to check whether an image fits into memory, or to preallocate a buffer for
+    /// [`read_image_into`](Self::read_image_into).
+    pub fn decoded_size(&mut self) -> TiffResult<usize> {
+        let num_bands: usize = self.num_samples()?;
+        let (width, height): (u32, u32) = self.dimensions()?;
+        let bytes_per_sample: usize = self.bits_per_sample()?[0].div_ceil(8) as usize;
+
+        Ok(num_bands * height as usize * width as usize * bytes_per_sample)
     }
 
-    #[tokio::test]
-    async fn test_cogreader_ndarray() {
-        let cog_url: &str = "https://github.com/rasterio/rasterio/raw/1.3.9/tests/data/float32.tif";
-        let tif_url = Url::parse(cog_url).unwrap();
-        let (store, location) = parse_url(&tif_url).unwrap();
+    /// Decode GeoTIFF image into a flat buffer of little-endian bytes, along with the
+    /// [`DataType`] of the samples in the file and the shape (band, height, width) of the
+    /// image, e.g. for FFI consumers that reinterpret the bytes themselves.
+    ///
+    /// Like [`read_arrow`], the samples keep the dtype of the file without conversion, also
+    /// for images with sparse tiles (or strips). As with [`ndarray`](Self::ndarray), the
+    /// image is flipped and/or transposed according to its
+    /// [`orientation`](Self::orientation), so the shape is that of the visual image.
+    #[allow(clippy::type_complexity)]
+    pub fn read_raw(&mut self) -> TiffResult<(Vec<u8>, DataType, (usize, usize, usize))> {
+        let dtype: DataType = self.data_type()?;
+        let (bytes, shape): (Vec<u8>, (usize, usize, usize)) = match dtype {
+            DataType::UInt8 => raw_parts(self.decode_ndarray::<u8>()?),
+            DataType::UInt16 => raw_parts(self.decode_ndarray::<u16>()?),
+            DataType::UInt32 => raw_parts(self.decode_ndarray::<u32>()?),
+            DataType::UInt64 => raw_parts(self.decode_ndarray::<u64>()?),
+            DataType::Int8 => raw_parts(self.decode_ndarray::<i8>()?),
+            DataType::Int16 => raw_parts(self.decode_ndarray::<i16>()?),
+            DataType::Int32 => raw_parts(self.decode_ndarray::<i32>()?),
+            DataType::Int64 => raw_parts(self.decode_ndarray::<i64>()?),
+            DataType::Float32 => raw_parts(self.decode_ndarray::<f32>()?),
+            DataType::Float64 => raw_parts(self.decode_ndarray::<f64>()?),
+        };
 
-        let result = store.get(&location).await.unwrap();
-        let bytes = result.bytes().await.unwrap();
-        let stream = Cursor::new(bytes);
+        Ok((bytes, dtype, shape))
+    }
 
-        let mut reader = CogReader::new(stream).unwrap();
-        let array = reader.ndarray::<f32>().unwrap();
+    /// Decode GeoTIFF image into a caller-provided buffer in (band, height, width) order,
+    /// avoiding the allocation of a new array on every call.
+    ///
+    /// The image is decoded one tile (or strip) at a time into `out`, whose length must be
+    /// equal to `bands * height * width`, otherwise an error is returned. As with
+    /// [`ndarray`](Self::ndarray), the height and width are those of the visual image,
+    /// flipped and/or transposed according to its [`orientation`](Self::orientation).
+    ///
+    /// All bands are decoded as stored in row-major order, so an error is returned if the
+    /// reader was built with a band selection, scale and offset, or column-major order (see
+    /// [`CogReaderBuilder`]), use [`ndarray`](Self::ndarray) for those instead. Lossy
+    /// images are rejected as with [`ndarray`](Self::ndarray) if exact values are required.
+    pub fn read_image_into<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        out: &mut [T],
+    ) -> TiffResult<()> {
+        self.check_lossless()?;
+        if self.options.bands.is_some()
+            || self.options.apply_scale_offset
+            || self.options.order == Order::ColumnMajor
+        {
+            return Err(TiffError::FormatError(TiffFormatError::Format(
+                String::from(
+                    "Band selection, scale and offset, and column-major order are not supported \
+                 when decoding into a buffer, use ndarray instead",
+                ),
+            )));
+        }
 
-        assert_eq!(array.shape(), [1, 2, 3]);
-        assert_eq!(array, array![[[1.41, 1.23, 0.78], [0.32, -0.23, -1.88]]])
+        // Check that the output buffer has the right size
+        let num_bands: usize = self.num_samples()?;
+        let (width, height): (u32, u32) = self.oriented_dimensions()?;
+        let expected_len: usize = num_bands * height as usize * width as usize;
+        if out.len() != expected_len {
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "Output buffer has length {}, expected {expected_len} \
+                 (bands={num_bands} * height={height} * width={width})",
+                out.len()
+            ))));
+        }
+        let mut out_array: ArrayViewMut3<T> =
+            ArrayViewMut3::from_shape((num_bands, height as usize, width as usize), out)
+                .map_err(|_| TiffFormatError::InconsistentSizesEncountered)?;
+
+        self.decode_chunks_into(&mut out_array, |_| {})
     }
 
-    #[tokio::test]
-    async fn test_cogreader_transform() {
-        let cog_url: &str =
-            "https://github.com/cogeotiff/rio-tiler/raw/6.4.0/tests/fixtures/cog_nodata_nan.tif";
-        let tif_url = Url::parse(cog_url).unwrap();
-        let (store, location) = parse_url(&tif_url).unwrap();
+    /// Decode GeoTIFF image to an [`ndarray::Array`] of shape (band, height, width), while
+    /// reporting progress, e.g. to show a progress bar while decoding a large image.
+    ///
+    /// The image is decoded one tile (or strip) at a time, and `progress` is called after
+    /// each one with the fraction of tiles decoded so far, from 0 (exclusive) to 1.0. This
+    /// is slower than [`ndarray`](Self::ndarray) for stripped images, which can otherwise
+    /// be decoded in one go. Each tile is flipped and/or transposed according to the
+    /// image's [`orientation`](Self::orientation), as with [`ndarray`](Self::ndarray).
+    pub fn read_image_with_progress<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        progress: impl FnMut(f32),
+    ) -> TiffResult<Array3<T>> {
+        let num_bands: usize = self.num_samples()?;
+        let (width, height): (u32, u32) = self.oriented_dimensions()?;
+        let mut array_data: Array3<T> = Array3::from_elem(
+            (num_bands, height as usize, width as usize),
+            T::from_u8(0).unwrap(),
+        );
 
-        let result = store.get(&location).await.unwrap();
-        let bytes = result.bytes().await.unwrap();
-        let stream = Cursor::new(bytes);
+        self.decode_chunks_into(&mut array_data.view_mut(), progress)?;
 
-        let mut reader = CogReader::new(stream).unwrap();
-        let transform = reader.transform().unwrap();
+        Ok(array_data)
+    }
 
-        assert_eq!(
+    /// Decode every chunk (tile or strip) and copy it to its position in the visual output
+    /// array of shape (band, height, width), calling `progress` with the fraction of chunks
+    /// decoded after each one
+    fn decode_chunks_into<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        out_array: &mut ArrayViewMut3<T>,
+        mut progress: impl FnMut(f32),
+    ) -> TiffResult<()> {
+        let (chunks_across, chunks_down): (u32, u32) = self.chunk_grid()?;
+        let num_chunks: u32 = chunks_across * chunks_down;
+        for chunk_index in 0..num_chunks {
+            let (tile_pos, tile): (TilePos, Array3<T>) = self.read_oriented_chunk(chunk_index)?;
+            let row_off = tile_pos.row_off as usize;
+            let col_off = tile_pos.col_off as usize;
+            out_array
+                .slice_mut(s![
+                    ..,
+                    row_off..row_off + tile_pos.height as usize,
+                    col_off..col_off + tile_pos.width as usize
+                ])
+                .assign(&tile);
+            progress((chunk_index + 1) as f32 / num_chunks as f32);
+        }
+
+        Ok(())
+    }
+
+    /// Iterate over the GeoTIFF image one decoded tile (or strip) at a time, in row-major
+    /// order. Each item is the [`TilePos`] of the tile within the image, and the tile's
+    /// pixel data as an [`ndarray::Array`] of shape (band, height, width).
+    ///
+    /// Only one tile is held in memory at a time, so this can be used to process images
+    /// that are too large to decode in full. Tiles along the right and bottom edges of
+    /// the image may be smaller than the nominal tile size, and are returned with their
+    /// true decoded dimensions. As with [`ndarray`](Self::ndarray), each tile and its
+    /// position are flipped and/or transposed according to the image's
+    /// [`orientation`](Self::orientation), so tiles may not come in row-major order of the
+    /// visual image.
+    pub fn tiles<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+    ) -> impl Iterator<Item = TiffResult<(TilePos, Array3<T>)>> + '_ {
+        let mut chunk_index: u32 = 0;
+        let mut finished: bool = false;
+
+        std::iter::from_fn(move || {
+            if finished {
+                return None;
+            }
+            let num_chunks: u32 = match self.chunk_grid() {
+                Ok((chunks_across, chunks_down)) => chunks_across * chunks_down,
+                Err(err) => {
+                    finished = true;
+                    return Some(Err(err));
+                }
+            };
+            if chunk_index >= num_chunks {
+                return None;
+            }
+
+            let tile = self.read_oriented_chunk(chunk_index);
+            chunk_index += 1;
+            Some(tile)
+        })
+    }
+
+    /// Iterate over the GeoTIFF image one full-width row of pixels at a time, from top to
+    /// bottom. Each item is the row's pixel data as an [`ndarray::Array`] of shape
+    /// (band, width).
+    ///
+    /// The strips (or tiles) are decoded lazily, one row of strips (or tiles) at a time,
+    /// which is buffered to yield its individual pixel rows. So memory use is bounded by the
+    /// strip (or tile) height, e.g. for row-oriented processing of rasters that are too
+    /// tall to decode in full. As with [`ndarray`](Self::ndarray), the rows are those of the
+    /// visual image, flipped and/or transposed according to its
+    /// [`orientation`](Self::orientation).
+    pub fn rows<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+    ) -> impl Iterator<Item = TiffResult<Array2<T>>> + '_ {
+        let mut row: u32 = 0;
+        let mut buffer: Option<(u32, Array3<T>)> = None; // (first row, rows of chunks)
+        let mut finished: bool = false;
+
+        std::iter::from_fn(move || {
+            if finished {
+                return None;
+            }
+            let (orientation, height): (u16, u32) =
+                match (self.orientation(), self.oriented_dimensions()) {
+                    (Ok(orientation), Ok((_width, height))) => (orientation, height),
+                    (Err(err), _) | (_, Err(err)) => {
+                        finished = true;
+                        return Some(Err(err));
+                    }
+                };
+            if row >= height {
+                return None;
+            }
+
+            // Decode the next row of chunks once all rows of the buffered one are yielded
+            let is_buffered: bool = matches!(&buffer, Some((start_row, rows))
+                if row < start_row + rows.len_of(Axis(1)) as u32);
+            if !is_buffered {
+                // Visual rows are stored as columns for orientations 5 to 8, and counted
+                // from the bottom of the stored chunks when flipped
+                let (chunk_width, chunk_height): (u32, u32) = self.decoder.chunk_dimensions();
+                let rows_per_chunk: u32 = match (5..=8).contains(&orientation) {
+                    true => chunk_width,
+                    false => chunk_height,
+                };
+                let num_rows: u32 = match orientation_flips(orientation) {
+                    (true, _) => (height - 1 - row) % rows_per_chunk + 1,
+                    (false, _) => rows_per_chunk - row % rows_per_chunk,
+                };
+                match self.read_rows(row, num_rows) {
+                    Ok(rows) => buffer = Some((row, rows)),
+                    Err(err) => {
+                        finished = true;
+                        return Some(Err(err));
+                    }
+                }
+            }
+
+            let (start_row, rows) = buffer.as_ref()?;
+            let pixel_row: Array2<T> = rows
+                .index_axis(Axis(1), (row - start_row) as usize)
+                .to_owned();
+            row += 1;
+            Some(Ok(pixel_row))
+        })
+    }
+
+    /// Decode a single internal tile of a tiled GeoTIFF image to an [`ndarray::Array`] of
+    /// shape (band, height, width), where `tile_col` and `tile_row` are the zero-based
+    /// column and row indices of the tile in the image's tile grid.
+    ///
+    /// Tiles along the right and bottom edges of the image may be smaller than the nominal
+    /// tile size. Returns an error if the image is stored in strips rather than tiles, if
+    /// the tile indices are out of range, or if the image is flipped or rotated (see
+    /// [`orientation`](Self::orientation)), since the tile grid is that of the stored image.
+    pub fn read_tile<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        tile_col: u32,
+        tile_row: u32,
+    ) -> TiffResult<Array3<T>> {
+        if matches!(self.decoder.get_chunk_type(), ChunkType::Strip) {
+            return Err(TiffError::FormatError(TiffFormatError::Format(
+                String::from("Image is stored in strips, not tiles"),
+            )));
+        }
+        self.check_default_orientation("Tile indices")?;
+
+        let (tiles_across, tiles_down): (u32, u32) = self.chunk_grid()?;
+        if tile_col >= tiles_across || tile_row >= tiles_down {
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "Tile (col={tile_col}, row={tile_row}) is out of range of tile grid with \
+                 {tiles_across} columns and {tiles_down} rows"
+            ))));
+        }
+
+        let (_tile_pos, array_data): (TilePos, Array3<T>) =
+            self.read_chunk_ndarray(tile_row * tiles_across + tile_col)?;
+
+        Ok(array_data)
+    }
+
+    /// Decode a rectangular block of internal tiles of a tiled GeoTIFF image, stitched
+    /// together into one [`ndarray::Array`] of shape (band, height, width), where
+    /// `col_range` and `row_range` are the (end-exclusive) ranges of column and row indices
+    /// of the tiles in the image's tile grid, e.g. to read a quadtree node that covers
+    /// several tiles.
+    ///
+    /// Tiles along the right and bottom edges of the image are clipped to the image extent.
+    /// Returns an error if the image is stored in strips rather than tiles, if either range
+    /// is empty or out of range of the tile grid, or if the image is flipped or rotated (see
+    /// [`orientation`](Self::orientation)), since the tile grid is that of the stored image.
+    pub fn read_tile_range<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        col_range: Range<u32>,
+        row_range: Range<u32>,
+    ) -> TiffResult<Array3<T>> {
+        if matches!(self.decoder.get_chunk_type(), ChunkType::Strip) {
+            return Err(TiffError::FormatError(TiffFormatError::Format(
+                String::from("Image is stored in strips, not tiles"),
+            )));
+        }
+        self.check_default_orientation("Tile indices")?;
+
+        let (tiles_across, tiles_down): (u32, u32) = self.chunk_grid()?;
+        if col_range.is_empty()
+            || row_range.is_empty()
+            || col_range.end > tiles_across
+            || row_range.end > tiles_down
+        {
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "Tile range (cols={col_range:?}, rows={row_range:?}) is empty or out of range \
+                 of tile grid with {tiles_across} columns and {tiles_down} rows"
+            ))));
+        }
+
+        // Convert tile range to a pixel window, clipped to the image extent
+        let (image_width, image_height): (u32, u32) = self.dimensions()?;
+        let (tile_width, tile_height): (u32, u32) = self.decoder.chunk_dimensions();
+        let (col_off, row_off): (u32, u32) =
+            (col_range.start * tile_width, row_range.start * tile_height);
+        let col_end: u32 = col_range.end.saturating_mul(tile_width).min(image_width);
+        let row_end: u32 = row_range.end.saturating_mul(tile_height).min(image_height);
+
+        self.read_window(col_off, row_off, col_end - col_off, row_end - row_off)
+    }
+
+    /// Decode a rectangular window of the GeoTIFF image to an [`ndarray::Array`] of shape
+    /// (band, height, width), where `col_off` and `row_off` are the pixel offsets of the
+    /// window's upper-left corner from the image's upper-left corner.
+    ///
+    /// As with [`ndarray`](Self::ndarray), the window is taken from the visual image, which
+    /// is flipped and/or transposed according to its [`orientation`](Self::orientation).
+    /// Only the tiles (or strips) that intersect the window are decoded. Returns an error
+    /// if the window extends beyond the bounds of the image.
+    pub fn read_window<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        col_off: u32,
+        row_off: u32,
+        width: u32,
+        height: u32,
+    ) -> TiffResult<Array3<T>> {
+        // Check that window is within the image, also if its end overflows
+        let orientation: u16 = self.orientation()?;
+        let (image_width, image_height): (u32, u32) = self.oriented_dimensions()?;
+        let is_in_bounds: bool = matches!(
+            (col_off.checked_add(width), row_off.checked_add(height)),
+            (Some(col_end), Some(row_end)) if col_end <= image_width && row_end <= image_height
+        );
+        if !is_in_bounds {
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "Window (col_off={col_off}, row_off={row_off}, width={width}, height={height}) \
+                 is out of bounds of image with width={image_width}, height={image_height}"
+            ))));
+        }
+
+        // Decode the window of the stored image that is flipped and/or transposed into it
+        let window = TilePos {
+            col_off,
+            row_off,
+            width,
+            height,
+        };
+        let stored_window: TilePos =
+            stored_tile_pos(window, orientation, (image_width, image_height));
+        let array_data: Array3<T> = self.read_stored_window(stored_window)?;
+
+        Ok(apply_orientation(array_data, orientation))
+    }
+
+    /// Decode a rectangular window of the image as stored in the TIFF file, regardless of
+    /// its orientation, to an [`ndarray::Array`] of shape (band, height, width). The window
+    /// must be within the bounds of the stored image.
+    fn read_stored_window<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        window: TilePos,
+    ) -> TiffResult<Array3<T>> {
+        let TilePos {
+            col_off,
+            row_off,
+            width,
+            height,
+        } = window;
+        let (col_end, row_end): (u32, u32) = (col_off + width, row_off + height);
+
+        let num_bands: usize = self.num_samples()?;
+        let mut window: Array3<T> = Array3::from_elem(
+            (num_bands, height as usize, width as usize),
+            T::from_u8(0).unwrap(),
+        );
+
+        // Decode every chunk that intersects the window, and copy over the overlapping part
+        let (chunks_across, _chunks_down): (u32, u32) = self.chunk_grid()?;
+        let (chunk_width, chunk_height): (u32, u32) = self.decoder.chunk_dimensions();
+        for chunk_row in row_off / chunk_height..row_end.div_ceil(chunk_height) {
+            for chunk_col in col_off / chunk_width..col_end.div_ceil(chunk_width) {
+                let chunk_index: u32 = chunk_row * chunks_across + chunk_col;
+                let (tile_pos, tile): (TilePos, Array3<T>) =
+                    self.read_chunk_ndarray(chunk_index)?;
+
+                // Get intersection of chunk and window in image pixel coordinates
+                let x_start: u32 = col_off.max(tile_pos.col_off);
+                let x_end: u32 = col_end.min(tile_pos.col_off + tile_pos.width);
+                let y_start: u32 = row_off.max(tile_pos.row_off);
+                let y_end: u32 = row_end.min(tile_pos.row_off + tile_pos.height);
+
+                window
+                    .slice_mut(s![
+                        ..,
+                        (y_start - row_off) as usize..(y_end - row_off) as usize,
+                        (x_start - col_off) as usize..(x_end - col_off) as usize
+                    ])
+                    .assign(&tile.slice(s![
+                        ..,
+                        (y_start - tile_pos.row_off) as usize..(y_end - tile_pos.row_off) as usize,
+                        (x_start - tile_pos.col_off) as usize..(x_end - tile_pos.col_off) as usize
+                    ]));
+            }
+        }
+
+        Ok(window)
+    }
+
+    /// Decode a range of full-width rows of the GeoTIFF image to an [`ndarray::Array`] of
+    /// shape (band, rows, width), starting from `start_row`.
+    ///
+    /// Only the strips (or tiles) that intersect the rows are decoded, so for stripped
+    /// images (see [`rows_per_strip`](Self::rows_per_strip)) this avoids decoding the whole
+    /// image. The range is clamped to the image height, so the returned array can have fewer
+    /// than `num_rows` rows. Returns an error if `start_row` is beyond the last row.
+    pub fn read_rows<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        start_row: u32,
+        num_rows: u32,
+    ) -> TiffResult<Array3<T>> {
+        let (width, height): (u32, u32) = self.oriented_dimensions()?;
+        if start_row >= height {
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "Start row {start_row} is out of bounds of image with height={height}"
+            ))));
+        }
+        let num_rows: u32 = num_rows.min(height - start_row);
+
+        self.read_window(0, start_row, width, num_rows)
+    }
+
+    /// Decode the part of the GeoTIFF image within a geographic/projected bounding box
+    /// (in the file's own coordinate reference system) to an [`ndarray::Array`] of shape
+    /// (band, height, width).
+    ///
+    /// The bounding box is converted to a pixel window using the inverse of the affine
+    /// [`transform`](Self::transform), expanded outwards to whole pixels, and clamped to the
+    /// image extent. Returns the decoded data along with the affine transform of the
+    /// cropped window, or an error if the bounding box does not intersect the image. Also
+    /// returns an error if the image is flipped or rotated (see
+    /// [`orientation`](Self::orientation)), since the transform maps the pixels of the
+    /// stored image.
+    pub fn read_bbox<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        min_x: f64,
+        min_y: f64,
+        max_x: f64,
+        max_y: f64,
+    ) -> TiffResult<(Array3<T>, AffineTransform<f64>)> {
+        self.check_default_orientation("The affine transform")?;
+        let transform: AffineTransform<f64> = self.transform()?;
+
+        // Convert corners of bounding box to pixel (row, col) positions
+        let corners: Vec<(f64, f64)> = [
+            (min_x, min_y),
+            (min_x, max_y),
+            (max_x, min_y),
+            (max_x, max_y),
+        ]
+        .into_iter()
+        .map(|(x, y)| self.world_to_pixel(x, y))
+        .collect::<TiffResult<_>>()?;
+        let row_min: f64 = corners
+            .iter()
+            .map(|(row, _)| *row)
+            .fold(f64::INFINITY, f64::min);
+        let row_max: f64 = corners
+            .iter()
+            .map(|(row, _)| *row)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let col_min: f64 = corners
+            .iter()
+            .map(|(_, col)| *col)
+            .fold(f64::INFINITY, f64::min);
+        let col_max: f64 = corners
+            .iter()
+            .map(|(_, col)| *col)
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        // Clamp pixel window to the image extent
+        let (image_width, image_height): (u32, u32) = self.dimensions()?;
+        let col_start: f64 = col_min.floor().max(0.0);
+        let col_end: f64 = col_max.ceil().min(image_width as f64);
+        let row_start: f64 = row_min.floor().max(0.0);
+        let row_end: f64 = row_max.ceil().min(image_height as f64);
+        if col_start >= col_end || row_start >= row_end {
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "Bounding box ({min_x}, {min_y}, {max_x}, {max_y}) does not intersect the image"
+            ))));
+        }
+
+        // Decode pixel window
+        let array_data: Array3<T> = self.read_window(
+            col_start as u32,
+            row_start as u32,
+            (col_end - col_start) as u32,
+            (row_end - row_start) as u32,
+        )?;
+
+        // Shift origin of affine transform to the window's upper-left corner
+        let origin: Coord<f64> = transform.apply(Coord {
+            x: col_start,
+            y: row_start,
+        });
+        let window_transform = AffineTransform::new(
+            transform.a(),
+            transform.b(),
+            origin.x,
+            transform.d(),
+            transform.e(),
+            origin.y,
+        );
+
+        Ok((array_data, window_transform))
+    }
+
+    /// Decode the GeoTIFF image resampled to an [`ndarray::Array`] of shape
+    /// (band, out_height, out_width), e.g. to generate fixed-size thumbnails.
+    ///
+    /// The smallest overview of the selected image that is at least as large as the
+    /// requested output size is decoded (or the full-resolution image if no overview is
+    /// large enough), and then resampled to the output size using the given [`Resampling`]
+    /// method. Interpolated values are truncated towards zero for integer dtypes. As with
+    /// [`ndarray`](Self::ndarray), the output size is that of the visual image, which is
+    /// flipped and/or transposed according to its [`orientation`](Self::orientation), and
+    /// the read options of a [`CogReaderBuilder`] are applied, with the scale and offset
+    /// of the selected image also used for its overviews.
+    pub fn read_resampled<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        out_width: u32,
+        out_height: u32,
+        method: Resampling,
+    ) -> TiffResult<Array3<T>> {
+        if out_width == 0 || out_height == 0 {
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "Output size (width={out_width}, height={out_height}) must be non-zero"
+            ))));
+        }
+
+        self.check_lossless()?;
+        let scale_offset: Option<Vec<(f64, f64)>> = match self.options.apply_scale_offset {
+            true => Some(self.scale_offset()?),
+            false => None,
+        };
+
+        // Find the smallest overview that is still larger than the output size
+        let image_ifd_index: usize = self.ifd_index;
+        let mut source_ifd_index: usize = image_ifd_index;
+        for overview_ifd_index in self.overview_ifd_indices()? {
+            self.seek_to_ifd(overview_ifd_index)?;
+            let (width, height): (u32, u32) = self.oriented_dimensions()?;
+            if width < out_width || height < out_height {
+                break;
+            }
+            source_ifd_index = overview_ifd_index;
+        }
+
+        // Decode source image, and go back to the selected image
+        self.seek_to_ifd(source_ifd_index)?;
+        let source_data: TiffResult<Array3<f64>> =
+            self.decode_with_options(scale_offset.as_deref());
+        self.seek_to_ifd(image_ifd_index)?;
+
+        let resampled: Array3<f64> = resample(
+            &source_data?,
+            out_width as usize,
+            out_height as usize,
+            method,
+        );
+        let array_data: Array3<T> = match scale_offset {
+            Some(_) => scaled_to_dtype(resampled)?,
+            None => resampled.mapv(|value| T::from_f64(value).unwrap_or(T::from_u8(0).unwrap())),
+        };
+
+        if self.options.order == Order::ColumnMajor {
+            return to_fortran_order(array_data);
+        }
+
+        Ok(array_data)
+    }
+
+    /// Decode a preview (quicklook) of the GeoTIFF image whose largest dimension is at most
+    /// `max_dim` pixels, keeping the aspect ratio of the image, e.g. for thumbnails in
+    /// catalog UIs.
+    ///
+    /// The smallest overview that is still at least as large as the preview is decoded
+    /// (see [`read_resampled`](Self::read_resampled)), and downsampled to the preview size
+    /// with [`Resampling::Average`]. Images that already fit within `max_dim` are decoded
+    /// at full resolution without resampling, with [`ndarray`](Self::ndarray). Either way,
+    /// the preview is of the visual image with the read options applied.
+    pub fn read_preview<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        max_dim: u32,
+    ) -> TiffResult<Array3<T>> {
+        let (width, height): (u32, u32) = self.oriented_dimensions()?;
+        if width.max(height) <= max_dim {
+            return self.ndarray();
+        }
+
+        // Scale the largest dimension down to max_dim, keeping at least one pixel
+        let scale: f64 = max_dim as f64 / width.max(height) as f64;
+        let out_width: u32 = ((width as f64 * scale).round() as u32).max(1);
+        let out_height: u32 = ((height as f64 * scale).round() as u32).max(1);
+
+        self.read_resampled(out_width, out_height, Resampling::Average)
+    }
+
+    /// Decode the selected image and all of its reduced-resolution overviews, from the full
+    /// resolution level to the smallest overview, e.g. for multiscale (OME-Zarr) pyramids.
+    ///
+    /// Each level is returned as an [`ndarray::Array`] of shape (band, height, width), along
+    /// with its affine transform. Overviews usually have no georeferencing tags of their own,
+    /// so their transforms are derived from the full resolution
+    /// [`transform`](Self::transform) by scaling the pixel size with the ratio of the image
+    /// sizes (e.g. doubling for each power-of-two overview), keeping the same origin.
+    pub fn read_pyramid<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+    ) -> TiffResult<Vec<(Array3<T>, AffineTransform<f64>)>> {
+        let transform: AffineTransform<f64> = self.transform()?;
+        let (width, height): (u32, u32) = self.dimensions()?;
+
+        let image_ifd_index: usize = self.ifd_index;
+        let mut ifd_indices: Vec<usize> = vec![image_ifd_index];
+        ifd_indices.extend(self.overview_ifd_indices()?);
+
+        let mut pyramid: Vec<(Array3<T>, AffineTransform<f64>)> = vec![];
+        for ifd_index in ifd_indices {
+            self.seek_to_ifd(ifd_index)?;
+            let level_data: TiffResult<Array3<T>> = self.ndarray();
+            let level_dimensions: TiffResult<(u32, u32)> = self.dimensions();
+            self.seek_to_ifd(image_ifd_index)?;
+
+            // Scale pixel size by the ratio of the full resolution to the level's size
+            let (level_width, level_height): (u32, u32) = level_dimensions?;
+            let x_factor: f64 = width as f64 / level_width as f64;
+            let y_factor: f64 = height as f64 / level_height as f64;
+            let level_transform = AffineTransform::new(
+                transform.a() * x_factor,
+                transform.b() * y_factor,
+                transform.xoff(),
+                transform.d() * x_factor,
+                transform.e() * y_factor,
+                transform.yoff(),
+            );
+            pyramid.push((level_data?, level_transform));
+        }
+
+        Ok(pyramid)
+    }
+
+    /// Number of reduced-resolution overviews of the selected image
+    pub fn num_overviews(&mut self) -> TiffResult<usize> {
+        Ok(self.overview_ifd_indices()?.len())
+    }
+
+    /// Decode a reduced-resolution overview of the selected image to an [`ndarray::Array`]
+    /// of shape (band, height, width), where `level` 0 is the largest overview (usually
+    /// half the size of the image) and [`num_overviews`](Self::num_overviews) - 1 is the
+    /// smallest. Returns an error if there is no overview at that level.
+    pub fn read_overview<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        level: usize,
+    ) -> TiffResult<Array3<T>> {
+        let overview_ifd_indices: Vec<usize> = self.overview_ifd_indices()?;
+        let Some(&overview_ifd_index) = overview_ifd_indices.get(level) else {
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "Overview level {level} does not exist, image has {} overviews",
+                overview_ifd_indices.len()
+            ))));
+        };
+
+        // Decode overview, and go back to the selected image
+        let image_ifd_index: usize = self.ifd_index;
+        self.seek_to_ifd(overview_ifd_index)?;
+        let overview_data: TiffResult<Array3<T>> = self.ndarray();
+        self.seek_to_ifd(image_ifd_index)?;
+
+        overview_data
+    }
+
+    /// Indices of the Image File Directories (IFDs) holding the reduced-resolution overviews
+    /// of the selected image, from largest to smallest
+    fn overview_ifd_indices(&mut self) -> TiffResult<Vec<usize>> {
+        let overview_ifd_indices: Vec<usize> = self
+            .subfile_types()?
+            .iter()
+            .enumerate()
+            .skip(self.ifd_index + 1)
+            .take_while(|(_, subfile_type)| !is_full_image(**subfile_type))
+            .filter(|(_, subfile_type)| **subfile_type & SUBFILE_MASK == 0)
+            .map(|(ifd_index, _)| ifd_index)
+            .collect();
+
+        Ok(overview_ifd_indices)
+    }
+
+    /// Orientation of the image rows and columns relative to the visual image, read from
+    /// the Orientation TIFF tag (274). Defaults to 1 (top-left origin) if the tag is absent,
+    /// which is almost always the case for geospatial GeoTIFFs.
+    ///
+    /// Values 2 to 8 denote images that are stored flipped and/or rotated, e.g. 3 for an
+    /// image rotated by 180 degrees, or 6 for an image where the stored rows are the
+    /// visual columns (from the right).
+    pub fn orientation(&mut self) -> TiffResult<u16> {
+        let orientation: u16 = self
+            .decoder
+            .find_tag_unsigned(Tag::Orientation)?
+            .unwrap_or(1);
+
+        Ok(orientation)
+    }
+
+    /// Width and height of the visual image in pixels, i.e. with the stored width and height
+    /// swapped for orientations 5 to 8
+    fn oriented_dimensions(&mut self) -> TiffResult<(u32, u32)> {
+        let (width, height): (u32, u32) = self.dimensions()?;
+        if (5..=8).contains(&self.orientation()?) {
+            return Ok((height, width));
+        }
+
+        Ok((width, height))
+    }
+
+    /// Return an error for flipped or rotated images, for readers whose inputs (`what`)
+    /// refer to the pixels of the stored image rather than the visual image
+    fn check_default_orientation(&mut self, what: &str) -> TiffResult<()> {
+        let orientation: u16 = self.orientation()?;
+        if orientation != 1 {
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "{what} refer to the stored image, which is flipped and/or rotated \
+                 (orientation={orientation}), use read_window instead"
+            ))));
+        }
+
+        Ok(())
+    }
+
+    /// Compression method used to encode the image pixel data, read from the Compression
+    /// TIFF tag (259). Defaults to [`CompressionMethod::None`] if the tag is absent.
+    ///
+    /// Images that are uncompressed, or compressed with LZW, Deflate, PackBits or JPEG, can
+    /// be decoded. Other codecs (e.g. ZSTD, WebP, LERC or JPEG 2000) are not supported by
+    /// the underlying [`tiff`] decoder, and decoding them returns an error naming the codec.
+    pub fn compression(&mut self) -> TiffResult<CompressionMethod> {
+        let compression: u16 = self
+            .decoder
+            .find_tag_unsigned(Tag::Compression)?
+            .unwrap_or(1);
+
+        Ok(CompressionMethod::from_u16_exhaustive(compression))
+    }
+
+    /// Whether the image pixel data is compressed with a lossy codec, so that the decoded
+    /// values only approximate the original ones, and should not be used to compute
+    /// statistics that assume exact values.
+    ///
+    /// JPEG, JPEG 2000, LERC, WebP and JPEG XL compression are all treated as lossy, as
+    /// their lossless modes (if any) cannot be told apart from the Compression TIFF tag.
+    /// Use [`CogReaderBuilder::require_lossless`] to return an error when decoding such
+    /// images.
+    pub fn is_lossy_compression(&mut self) -> TiffResult<bool> {
+        let code: u16 = self.compression()?.to_u16();
+
+        Ok(matches!(code, 6 | 7 | 34712 | 34887 | 50001 | 50002))
+    }
+
+    /// Predictor applied to the image pixel data before compression, read from the
+    /// Predictor TIFF tag (317). Defaults to [`Predictor::None`] if the tag is absent.
+    ///
+    /// Horizontal differencing (2) and floating point (3) predictors are reversed by the
+    /// TIFF decoder, while any other value returns an error, as the decoded pixels would be
+    /// wrong.
+    pub fn predictor(&mut self) -> TiffResult<Predictor> {
+        let predictor: u16 = self.decoder.find_tag_unsigned(Tag::Predictor)?.unwrap_or(1);
+
+        Predictor::from_u16(predictor).ok_or(TiffError::FormatError(
+            TiffFormatError::UnknownPredictor(predictor),
+        ))
+    }
+
+    /// Check that the compression method is one that the TIFF decoder can decompress, so
+    /// that unsupported codecs (e.g. LERC or WebP) fail early with a clear error message.
+    /// This is returned as an I/O error of kind [`io::ErrorKind::Unsupported`], as the file
+    /// itself is valid.
+    ///
+    /// For JPEG compression, the quantization and Huffman tables shared by all tiles in the
+    /// JPEGTables TIFF tag (347) are passed on by the TIFF decoder to every tile decode.
+    fn check_compression(&mut self) -> TiffResult<()> {
+        let compression: CompressionMethod = self.compression()?;
+        match compression {
+            CompressionMethod::None
+            | CompressionMethod::LZW
+            | CompressionMethod::Deflate
+            | CompressionMethod::OldDeflate
+            | CompressionMethod::PackBits
+            | CompressionMethod::ModernJPEG => Ok(()),
+            _ => {
+                let code: u16 = compression.to_u16();
+                Err(TiffError::IoError(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!(
+                        "{} compression (method {code}) is not supported, only uncompressed, \
+                         LZW, Deflate, PackBits and JPEG compressed images can be decoded",
+                        compression_name(code)
+                    ),
+                )))
+            }
+        }
+    }
+
+    /// Check that the samples are not complex numbers (SampleFormat 5 or 6, e.g. in SAR
+    /// imagery) or of undefined type (SampleFormat 4), which the TIFF decoder cannot decode,
+    /// so that they fail early with a clear error message naming the data type.
+    ///
+    /// A missing SampleFormat tag is fine, as the samples then default to unsigned integers
+    /// as per the TIFF specification.
+    fn check_sample_format(&mut self) -> TiffResult<()> {
+        let complex_type: &str = match self.sample_format()?[0] {
+            SampleFormat::Unknown(5) => "CInt",
+            SampleFormat::Unknown(6) => "CFloat",
+            SampleFormat::Void => {
+                return Err(TiffError::FormatError(TiffFormatError::Format(
+                    "Undefined sample format (SampleFormat=4) is not supported, rewrite the \
+                     file with an explicit SampleFormat (e.g. unsigned integer) to decode it"
+                        .to_string(),
+                )))
+            }
+            _ => return Ok(()),
+        };
+        // Bits per sample covers both the real and imaginary parts of a complex number
+        let bits_per_sample: u16 = self.bits_per_sample()?[0];
+
+        Err(TiffError::FormatError(TiffFormatError::Format(format!(
+            "Complex sample format ({complex_type}{}) is not supported",
+            bits_per_sample / 2
+        ))))
+    }
+
+    /// Data type that the samples are decoded to, from the SampleFormat and BitsPerSample
+    /// TIFF tags, i.e. the smallest integer type that holds the bits of each sample, or the
+    /// float type of the same size.
+    fn data_type(&mut self) -> TiffResult<DataType> {
+        self.check_sample_format()?;
+        let sample_format: SampleFormat = self.sample_format()?[0];
+        let bits_per_sample: u16 = self.bits_per_sample()?[0];
+
+        let data_type: DataType = match (sample_format, bits_per_sample) {
+            (SampleFormat::Uint, 1..=8) => DataType::UInt8,
+            (SampleFormat::Uint, 9..=16) => DataType::UInt16,
+            (SampleFormat::Uint, 17..=32) => DataType::UInt32,
+            (SampleFormat::Uint, 33..=64) => DataType::UInt64,
+            (SampleFormat::Int, 1..=8) => DataType::Int8,
+            (SampleFormat::Int, 9..=16) => DataType::Int16,
+            (SampleFormat::Int, 17..=32) => DataType::Int32,
+            (SampleFormat::Int, 33..=64) => DataType::Int64,
+            (SampleFormat::IEEEFP, 32) => DataType::Float32,
+            (SampleFormat::IEEEFP, 64) => DataType::Float64,
+            _ => {
+                return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                    "Sample format {sample_format:?} with {bits_per_sample} bits per sample is \
+                     not supported"
+                ))))
+            }
+        };
+
+        Ok(data_type)
+    }
+
+    /// Data type of each sample (unsigned/signed integer or floating point), read from the
+    /// SampleFormat TIFF tag (339), with one entry per sample in a pixel. Defaults to
+    /// [`SampleFormat::Uint`] for every sample if the tag is absent.
+    ///
+    /// Values not defined by [`tiff`] (e.g. complex numbers, with SampleFormat 5 or 6) are
+    /// returned as [`SampleFormat::Unknown`].
+    pub fn sample_format(&mut self) -> TiffResult<Vec<SampleFormat>> {
+        let sample_formats: Vec<u16> = self.per_sample_tag(Tag::SampleFormat, 1)?;
+
+        Ok(sample_formats
+            .into_iter()
+            .map(SampleFormat::from_u16_exhaustive)
+            .collect())
+    }
+
+    /// Number of bits of each sample, read from the BitsPerSample TIFF tag (258), with one
+    /// entry per sample in a pixel. Defaults to 1 for every sample if the tag is absent.
+    ///
+    /// Unlike the dtype of a decoded array, this keeps odd bit depths (e.g. 12-bit packed
+    /// samples) and samples with different bit depths within a pixel.
+    pub fn bits_per_sample(&mut self) -> TiffResult<Vec<u16>> {
+        self.per_sample_tag(Tag::BitsPerSample, 1)
+    }
+
+    /// Values of a TIFF tag with one value per sample in a pixel (e.g. BitsPerSample),
+    /// where a single value applies to all samples, falling back to `default` for every
+    /// sample if the tag is absent
+    fn per_sample_tag(&mut self, tag: Tag, default: u16) -> TiffResult<Vec<u16>> {
+        let samples_per_pixel: usize = self.samples_per_pixel()?;
+        let values: Vec<u16> = match self.decoder.find_tag_unsigned_vec::<u16>(tag)? {
+            Some(values) if values.len() == 1 => vec![values[0]; samples_per_pixel],
+            Some(values) if !values.is_empty() => values,
+            _ => vec![default; samples_per_pixel],
+        };
+
+        Ok(values)
+    }
+
+    /// Color type of the image, i.e. the photometric interpretation of the samples along
+    /// with their bit depth. This can be used to distinguish e.g. an RGB image from a
+    /// 3-band multispectral image, or a grayscale image from a paletted one.
+    pub fn color_type(&mut self) -> TiffResult<ColorType> {
+        if let Some(color_type) = self.metadata.color_type {
+            return Ok(color_type);
+        }
+        let color_type: ColorType = self.decoder.colortype()?;
+        self.metadata.color_type = Some(color_type);
+
+        Ok(color_type)
+    }
+
+    /// Width and height of the image in pixels
+    fn dimensions(&mut self) -> TiffResult<(u32, u32)> {
+        if let Some(dimensions) = self.metadata.dimensions {
+            return Ok(dimensions);
+        }
+        let dimensions: (u32, u32) = self.decoder.dimensions()?;
+        self.metadata.dimensions = Some(dimensions);
+
+        Ok(dimensions)
+    }
+
+    /// Number of samples per pixel from the SamplesPerPixel TIFF tag (277), regardless of
+    /// the color type. Defaults to 1 if the tag is absent.
+    fn samples_per_pixel(&mut self) -> TiffResult<usize> {
+        let samples_per_pixel: u16 = self
+            .decoder
+            .find_tag_unsigned(Tag::SamplesPerPixel)?
+            .unwrap_or(1);
+
+        Ok(samples_per_pixel as usize)
+    }
+
+    /// Number of samples (bands) per pixel
+    fn num_samples(&mut self) -> TiffResult<usize> {
+        if let Some(num_samples) = self.metadata.num_samples {
+            return Ok(num_samples);
+        }
+        let color_type = self.color_type()?;
+        let num_samples: usize = match color_type {
+            ColorType::Multiband {
+                bit_depth: _,
+                num_samples,
+            } => num_samples as usize,
+            // Paletted images store one colormap index per pixel
+            ColorType::Gray(_) | ColorType::Palette(_) => 1,
+            ColorType::GrayA(_) | ColorType::RGB(_) | ColorType::RGBA(_) | ColorType::CMYK(_) => {
+                self.samples_per_pixel()?
+            }
+            // JPEG-compressed YCbCr samples are converted to RGB by the JPEG decoder, while
+            // other codecs would leave them as (possibly subsampled) YCbCr
+            ColorType::YCbCr(_) if self.compression()? == CompressionMethod::ModernJPEG => {
+                self.samples_per_pixel()?
+            }
+            _ => {
+                return Err(TiffError::UnsupportedError(
+                    TiffUnsupportedError::UnsupportedColorType(color_type),
+                ))
+            }
+        };
+        self.metadata.num_samples = Some(num_samples);
+
+        Ok(num_samples)
+    }
+
+    /// Width and height of the internal tiles, read from the TileWidth (322) and
+    /// TileLength (323) TIFF tags. Returns `None` if the image is stored in strips, see
+    /// [`rows_per_strip`](Self::rows_per_strip) instead.
+    pub fn tile_size(&mut self) -> TiffResult<Option<(u32, u32)>> {
+        let tile_width: Option<u32> = self.decoder.find_tag_unsigned(Tag::TileWidth)?;
+        let tile_length: Option<u32> = self.decoder.find_tag_unsigned(Tag::TileLength)?;
+
+        Ok(tile_width.zip(tile_length))
+    }
+
+    /// Number of rows in each strip, read from the RowsPerStrip TIFF tag (278). Defaults to
+    /// the image height (i.e. a single strip) if the tag is absent, and returns `None` if
+    /// the image is stored in tiles, see [`tile_size`](Self::tile_size) instead.
+    pub fn rows_per_strip(&mut self) -> TiffResult<Option<u32>> {
+        if matches!(self.decoder.get_chunk_type(), ChunkType::Tile) {
+            return Ok(None);
+        }
+        let (_width, height): (u32, u32) = self.dimensions()?;
+        let rows_per_strip: u32 = self
+            .decoder
+            .find_tag_unsigned(Tag::RowsPerStrip)?
+            .unwrap_or(height);
+
+        Ok(Some(rows_per_strip.min(height)))
+    }
+
+    /// Index of the alpha (transparency) band, if any, from the ExtraSamples TIFF tag
+    /// (338). Both associated (premultiplied) and unassociated alpha are detected, while
+    /// extra samples of unspecified meaning are not treated as alpha.
+    pub fn alpha_band_index(&mut self) -> TiffResult<Option<usize>> {
+        let Some(extra_samples) = self
+            .decoder
+            .find_tag_unsigned_vec::<u16>(Tag::ExtraSamples)?
+        else {
+            return Ok(None);
+        };
+        // Extra samples come after the color samples, i.e. they are the last bands
+        let first_extra_sample: usize = self
+            .samples_per_pixel()?
+            .saturating_sub(extra_samples.len());
+        let alpha_band_index: Option<usize> = extra_samples
+            .iter()
+            .position(|extra_sample| matches!(extra_sample, 1 | 2))
+            .map(|position| first_extra_sample + position);
+
+        Ok(alpha_band_index)
+    }
+
+    /// Color interpretation of each band, i.e. which display color (or alpha) it maps to,
+    /// from the PhotometricInterpretation (262) and ExtraSamples (338) TIFF tags.
+    ///
+    /// The color bands come first, as given by the PhotometricInterpretation (with YCbCr
+    /// images, e.g. JPEG compressed ones, being decoded to RGB), followed by the extra
+    /// samples, of which associated and unassociated alpha are detected. All other bands
+    /// (e.g. extra samples of unspecified meaning in multispectral images) are
+    /// [`ColorInterp::Undefined`].
+    pub fn color_interpretation(&mut self) -> TiffResult<Vec<ColorInterp>> {
+        let num_bands: usize = self.samples_per_pixel()?;
+        let mut color_interps: Vec<ColorInterp> = vec![ColorInterp::Undefined; num_bands];
+
+        let color_bands: &[ColorInterp] = match self
+            .decoder
+            .find_tag_unsigned::<u16>(Tag::PhotometricInterpretation)?
+        {
+            Some(0 | 1) => &[ColorInterp::Gray],
+            Some(2 | 6) => &[ColorInterp::Red, ColorInterp::Green, ColorInterp::Blue],
+            Some(3) => &[ColorInterp::Palette],
+            _ => &[],
+        };
+        for (color_interp, color_band) in color_interps.iter_mut().zip(color_bands) {
+            *color_interp = *color_band;
+        }
+        if let Some(alpha_band_index) = self.alpha_band_index()? {
+            color_interps[alpha_band_index] = ColorInterp::Alpha;
+        }
+
+        Ok(color_interps)
+    }
+
+    /// Color palette from the ColorMap TIFF tag (320) of a paletted image, as a list of
+    /// 16-bit (red, green, blue) colors indexed by pixel value. Returns `None` if the image
+    /// has no colormap.
+    pub fn colormap(&mut self) -> TiffResult<Option<Vec<[u16; 3]>>> {
+        let Some(values) = self.decoder.find_tag_unsigned_vec::<u16>(Tag::ColorMap)? else {
+            return Ok(None);
+        };
+        if values.len() % 3 != 0 {
+            return Err(TiffError::FormatError(TiffFormatError::InvalidTag));
+        }
+
+        // Colormap is stored as all red values, then all green values, then all blue values
+        let num_colors: usize = values.len() / 3;
+        let colormap: Vec<[u16; 3]> = (0..num_colors)
+            .map(|i| {
+                [
+                    values[i],
+                    values[num_colors + i],
+                    values[2 * num_colors + i],
+                ]
+            })
+            .collect();
+
+        Ok(Some(colormap))
+    }
+
+    /// Decode a paletted image to an 8-bit RGB(A) [`ndarray::Array`] of shape
+    /// (band, height, width), by mapping each pixel value through the
+    /// [`colormap`](Self::colormap), e.g. to display a categorical land-cover map.
+    ///
+    /// If the image has a [`nodata`](Self::nodata) value, a fourth alpha band is added, in
+    /// which pixels equal to the nodata value are transparent (0) and all others are opaque
+    /// (255). Otherwise, an RGB array with 3 bands is returned. Returns an error if the
+    /// image has no colormap, has more than one band, or has pixel values beyond the end of
+    /// the colormap.
+    pub fn read_rgba(&mut self) -> TiffResult<Array3<u8>> {
+        let Some(colormap) = self.colormap()? else {
+            return Err(TiffError::FormatError(TiffFormatError::Format(
+                String::from(
+                    "Image has no colormap (ColorMap TIFF tag) to map pixel values through",
+                ),
+            )));
+        };
+        let num_bands: usize = self.num_samples()?;
+        if num_bands != 1 {
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "Paletted image must have 1 band, but has {num_bands} bands"
+            ))));
+        }
+        let nodata: Option<f64> = self.nodata()?;
+
+        let index_data: Array3<u16> = self.decode_ndarray()?;
+        let indices = index_data.index_axis(Axis(0), 0);
+        if let Some(&index) = indices
+            .iter()
+            .find(|&&index| index as usize >= colormap.len())
+        {
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "Pixel value {index} is out of range for colormap with {} colors",
+                colormap.len()
+            ))));
+        }
+
+        // Scale 16-bit colors to 8-bit, e.g. 65535 to 255
+        let num_channels: usize = if nodata.is_some() { 4 } else { 3 };
+        let (height, width): (usize, usize) = indices.dim();
+        let rgba_data: Array3<u8> =
+            Array3::from_shape_fn((num_channels, height, width), |(channel, row, col)| {
+                let index: u16 = indices[[row, col]];
+                match channel {
+                    3 if Some(index as f64) == nodata => 0,
+                    3 => 255,
+                    _ => (colormap[index as usize][channel] / 257) as u8,
+                }
+            });
+
+        Ok(rgba_data)
+    }
+
+    /// Decode the alpha band to a validity mask of shape (height, width), where 255 marks
+    /// pixels with non-zero alpha (valid) and 0 marks fully transparent pixels (invalid).
+    /// Returns `None` if the image has no alpha band.
+    pub fn read_mask(&mut self) -> TiffResult<Option<Array2<u8>>> {
+        let Some(alpha_band_index) = self.alpha_band_index()? else {
+            return Ok(None);
+        };
+
+        let (width, height): (u32, u32) = self.oriented_dimensions()?;
+        let array_data: Array3<f64> = self.read_window(0, 0, width, height)?;
+        let mask: Array2<u8> = array_data
+            .index_axis(Axis(0), alpha_band_index)
+            .mapv(|alpha| if alpha > 0.0 { 255 } else { 0 });
+
+        Ok(Some(mask))
+    }
+
+    /// Decode the internal transparency mask of the selected image to a validity mask of
+    /// shape (height, width), where 255 marks valid pixels and 0 marks invalid ones. Returns
+    /// `None` if the image has no internal mask.
+    ///
+    /// Internal masks (as written by GDAL) are stored in a separate Image File Directory
+    /// (IFD) following the image, flagged as a transparency mask by the NewSubfileType TIFF
+    /// tag (254), usually with 1 bit per pixel. This is distinct from an
+    /// [alpha band](Self::read_mask) or a [`nodata`](Self::nodata) value.
+    pub fn read_internal_mask(&mut self) -> TiffResult<Option<Array2<u8>>> {
+        // The full-resolution mask is flagged as a mask but not as a reduced image
+        let mask_ifd_index: Option<usize> = self
+            .subfile_types()?
+            .iter()
+            .enumerate()
+            .skip(self.ifd_index + 1)
+            .take_while(|(_, subfile_type)| !is_full_image(**subfile_type))
+            .find(|(_, subfile_type)| {
+                **subfile_type & (SUBFILE_REDUCED_IMAGE | SUBFILE_MASK) == SUBFILE_MASK
+            })
+            .map(|(ifd_index, _)| ifd_index);
+        let Some(mask_ifd_index) = mask_ifd_index else {
+            return Ok(None);
+        };
+
+        // Decode mask, and go back to the selected image
+        let image_ifd_index: usize = self.ifd_index;
+        let (width, height): (u32, u32) = self.oriented_dimensions()?;
+        self.seek_to_ifd(mask_ifd_index)?;
+        let mask_dimensions: TiffResult<(u32, u32)> = self.oriented_dimensions();
+        let mask_data: TiffResult<Array3<f64>> = match mask_dimensions {
+            Ok((mask_width, mask_height)) if (mask_width, mask_height) == (width, height) => {
+                self.read_window(0, 0, width, height)
+            }
+            Ok((mask_width, mask_height)) => {
+                Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                    "Internal mask (width={mask_width}, height={mask_height}) does not match \
+                     image with width={width}, height={height}"
+                ))))
+            }
+            Err(err) => Err(err),
+        };
+        self.seek_to_ifd(image_ifd_index)?;
+
+        let mask: Array2<u8> =
+            mask_data?
+                .index_axis(Axis(0), 0)
+                .mapv(|value| if value > 0.0 { 255 } else { 0 });
+
+        Ok(Some(mask))
+    }
+
+    /// Number of chunks (tiles or strips) across and down the image
+    fn chunk_grid(&mut self) -> TiffResult<(u32, u32)> {
+        let (width, height): (u32, u32) = self.dimensions()?;
+        let (chunk_width, chunk_height): (u32, u32) = self.decoder.chunk_dimensions();
+
+        Ok((width.div_ceil(chunk_width), height.div_ceil(chunk_height)))
+    }
+
+    /// Position of a chunk (tile or strip) within the image. Chunks are indexed in row-major
+    /// spatial order (as in the TileOffsets/StripOffsets TIFF tags), regardless of the order
+    /// in which they are stored in the file.
+    fn chunk_position(&mut self, chunk_index: u32) -> TiffResult<TilePos> {
+        let (chunks_across, _chunks_down): (u32, u32) = self.chunk_grid()?;
+        let (chunk_width, chunk_height): (u32, u32) = self.decoder.chunk_dimensions();
+        let (width, height): (u32, u32) = self.decoder.chunk_data_dimensions(chunk_index);
+
+        Ok(TilePos {
+            col_off: (chunk_index % chunks_across) * chunk_width,
+            row_off: (chunk_index / chunks_across) * chunk_height,
+            width,
+            height,
+        })
+    }
+
+    /// Position, byte offset and byte count of every tile (or strip) in the file, in
+    /// row-major spatial order, e.g. for building a virtual (kerchunk/VirtualiZarr) store
+    /// that references the tiles directly.
+    ///
+    /// Tiles are matched to their position in the image by their index in the
+    /// TileOffsets/StripOffsets TIFF tags, so this is correct even for files where the
+    /// tiles are not stored in spatial order. Sparse tiles have an offset and byte count
+    /// of zero.
+    pub fn tile_offsets(&mut self) -> TiffResult<Vec<(TilePos, u64, u64)>> {
+        let offsets_tag: Tag = match self.decoder.get_chunk_type() {
+            ChunkType::Tile => Tag::TileOffsets,
+            ChunkType::Strip => Tag::StripOffsets,
+        };
+        let offsets: Vec<u64> = self.decoder.get_tag_u64_vec(offsets_tag)?;
+
+        offsets
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_index, offset)| {
+                let chunk_index = chunk_index as u32;
+                let tile_pos: TilePos = self.chunk_position(chunk_index)?;
+                let byte_count: u64 = self.chunk_byte_count(chunk_index)?;
+                Ok((tile_pos, offset, byte_count))
+            })
+            .collect()
+    }
+
+    /// Layout of the image's tiles (or strips) as the chunks of a Zarr array, so that a
+    /// virtual Zarr store (e.g. VirtualiZarr) can reference the tiles in the file directly.
+    ///
+    /// As the bands of a pixel are interleaved within each tile, the Zarr array has the
+    /// shape (height, width, band), with one chunk per tile. Sparse tiles are left out of
+    /// the chunk references, so that they are read as the fill value (i.e. the
+    /// [`nodata`](Self::nodata) value). Returns an error for band-separated (planar) images
+    /// and sample formats without a Zarr data type.
+    pub fn to_zarr_chunk_spec(&mut self) -> TiffResult<ZarrChunkSpec> {
+        if self
+            .decoder
+            .find_tag_unsigned::<u16>(Tag::PlanarConfiguration)?
+            == Some(2)
+        {
+            return Err(TiffError::UnsupportedError(
+                TiffUnsupportedError::UnsupportedPlanarConfig(Some(PlanarConfiguration::Planar)),
+            ));
+        }
+
+        // Data type as a NumPy array-protocol type string, e.g. "<f4"
+        let sample_format: SampleFormat = self.sample_format()?[0];
+        let bits_per_sample: u16 = self.bits_per_sample()?[0];
+        let kind: char = match sample_format {
+            SampleFormat::Uint => 'u',
+            SampleFormat::Int => 'i',
+            SampleFormat::IEEEFP => 'f',
+            _ => {
+                return Err(TiffError::UnsupportedError(
+                    TiffUnsupportedError::UnsupportedSampleFormat(vec![sample_format]),
+                ))
+            }
+        };
+        if !matches!(bits_per_sample, 8 | 16 | 32 | 64) {
+            return Err(TiffError::UnsupportedError(
+                TiffUnsupportedError::UnsupportedBitsPerChannel(bits_per_sample as u8),
+            ));
+        }
+        let byte_order: char = match (bits_per_sample, self.byte_order) {
+            (8, _) => '|',
+            (_, ByteOrder::LittleEndian) => '<',
+            (_, ByteOrder::BigEndian) => '>',
+        };
+        let dtype: String = format!("{byte_order}{kind}{}", bits_per_sample / 8);
+
+        let num_bands: usize = self.num_samples()?;
+        let (width, height): (u32, u32) = self.dimensions()?;
+        let (chunk_width, chunk_height): (u32, u32) = self.decoder.chunk_dimensions();
+        let chunks: Vec<ZarrChunkRef> = self
+            .tile_offsets()?
+            .into_iter()
+            .filter(|(_, _, byte_count)| *byte_count > 0)
+            .map(|(tile_pos, offset, length)| ZarrChunkRef {
+                index: [
+                    (tile_pos.row_off / chunk_height) as usize,
+                    (tile_pos.col_off / chunk_width) as usize,
+                    0,
+                ],
+                offset,
+                length,
+            })
+            .collect();
+
+        Ok(ZarrChunkSpec {
+            shape: [height as usize, width as usize, num_bands],
+            chunk_shape: [chunk_height as usize, chunk_width as usize, num_bands],
+            dtype,
+            fill_value: self.nodata()?,
+            compression: self.compression()?,
+            predictor: self.predictor()?,
+            chunks,
+        })
+    }
+
+    /// Check the integrity of every tile (or strip) in the image, e.g. to detect corruption
+    /// from a partial download, reporting the position of each tile that fails along with
+    /// the reason.
+    ///
+    /// The only per-tile checksum a COG can carry is GDAL's block trailer
+    /// (`BLOCK_TRAILER=LAST_4_BYTES_REPEATED` in the [`ghost_header`](Self::ghost_header)),
+    /// which repeats the last 4 bytes of each tile right after it. If present, and the file
+    /// was not edited afterwards (`KNOWN_INCOMPATIBLE_EDITION=NO`), each tile is checked
+    /// against its trailer and [`TileChecksums::Verified`] is returned. Otherwise, the
+    /// result is [`TileChecksums::NotAvailable`].
+    ///
+    /// Either way, tiles are also decoded, which fails for truncated or corrupt compressed
+    /// data, and for uncompressed images the byte count of each tile is checked against the
+    /// size of the decoded tile. Sparse tiles (with a byte count of zero) are skipped.
+    pub fn verify_tile_checksums(&mut self) -> TiffResult<TileChecksums> {
+        let has_trailers: bool = self.ghost_header.as_ref().is_some_and(|ghost_header| {
+            ghost_header.block_trailer_last_4_bytes_repeated
+                && !ghost_header.known_incompatible_edition
+        });
+        let offsets_tag: Tag = match self.decoder.get_chunk_type() {
+            ChunkType::Tile => Tag::TileOffsets,
+            ChunkType::Strip => Tag::StripOffsets,
+        };
+        let offsets: Vec<u64> = self.decoder.get_tag_u64_vec(offsets_tag)?;
+        let compression: CompressionMethod = self.compression()?;
+        let samples_per_pixel: u64 = self.samples_per_pixel()? as u64;
+        let bits_per_sample: u64 = self.bits_per_sample()?[0] as u64;
+        let (tile_width, tile_height): (u32, u32) = self.decoder.chunk_dimensions();
+
+        let mut failures: Vec<(TilePos, String)> = vec![];
+        let (chunks_across, chunks_down): (u32, u32) = self.chunk_grid()?;
+        for chunk_index in 0..chunks_across * chunks_down {
+            let tile_pos: TilePos = self.chunk_position(chunk_index)?;
+            let byte_count: u64 = self.chunk_byte_count(chunk_index)?;
+            if byte_count == 0 {
+                continue;
+            }
+
+            if has_trailers {
+                let offset: u64 =
+                    offsets
+                        .get(chunk_index as usize)
+                        .copied()
+                        .ok_or(TiffError::UsageError(UsageError::InvalidChunkIndex(
+                            chunk_index,
+                        )))?;
+                let (last_bytes, trailer): ([u8; 4], [u8; 4]) =
+                    self.read_block_trailer(offset, byte_count)?;
+                if last_bytes != trailer {
+                    failures.push((
+                        tile_pos,
+                        format!(
+                            "Block trailer {trailer:?} does not match the last 4 bytes \
+                             {last_bytes:?} of the tile"
+                        ),
+                    ));
+                    continue;
+                }
+            }
+
+            // Tiles are always stored at full size, while the last strip may be shorter
+            if compression == CompressionMethod::None {
+                let (width, height): (u32, u32) = match self.decoder.get_chunk_type() {
+                    ChunkType::Tile => (tile_width, tile_height),
+                    ChunkType::Strip => (tile_pos.width, tile_pos.height),
+                };
+                let row_bytes: u64 =
+                    (width as u64 * samples_per_pixel * bits_per_sample).div_ceil(8);
+                let expected_byte_count: u64 = row_bytes * height as u64;
+                if byte_count < expected_byte_count {
+                    failures.push((
+                        tile_pos,
+                        format!(
+                            "Byte count {byte_count} is less than the {expected_byte_count} \
+                             bytes of uncompressed data"
+                        ),
+                    ));
+                    continue;
+                }
+            }
+
+            if let Err(err) = self.read_chunk_ndarray::<f64>(chunk_index) {
+                failures.push((tile_pos, truncation_error(err).to_string()));
+            }
+        }
+
+        Ok(match has_trailers {
+            true => TileChecksums::Verified(failures),
+            false => TileChecksums::NotAvailable(failures),
+        })
+    }
+
+    /// Last 4 bytes of a chunk and the 4 bytes of GDAL's block trailer that follow it, which
+    /// should be identical. Chunks shorter than 4 bytes are zero-padded at the front.
+    fn read_block_trailer(
+        &mut self,
+        offset: u64,
+        byte_count: u64,
+    ) -> TiffResult<([u8; 4], [u8; 4])> {
+        let start: u64 = offset + byte_count.saturating_sub(4);
+        self.decoder.goto_offset_u64(start)?;
+        let mut bytes = [0u8; 8];
+        let num_last_bytes: usize = byte_count.min(4) as usize;
+        for byte in bytes[4 - num_last_bytes..].iter_mut() {
+            *byte = self.decoder.read_byte()?;
+        }
+        let (last_bytes, trailer) = bytes.split_at(4);
+        Ok((last_bytes.try_into().unwrap(), trailer.try_into().unwrap()))
+    }
+
+    /// Number of bytes used to store a chunk (tile or strip) in the file, which is zero for
+    /// sparse chunks that were not written (e.g. because they only contain NoData)
+    fn chunk_byte_count(&mut self, chunk_index: u32) -> TiffResult<u64> {
+        if self.chunk_byte_counts.is_none() {
+            let byte_counts_tag: Tag = match self.decoder.get_chunk_type() {
+                ChunkType::Tile => Tag::TileByteCounts,
+                ChunkType::Strip => Tag::StripByteCounts,
+            };
+            self.chunk_byte_counts = Some(self.decoder.get_tag_u64_vec(byte_counts_tag)?);
+        }
+
+        self.chunk_byte_counts
+            .as_ref()
+            .and_then(|byte_counts| byte_counts.get(chunk_index as usize).copied())
+            .ok_or(TiffError::UsageError(UsageError::InvalidChunkIndex(
+                chunk_index,
+            )))
+    }
+
+    /// Whether any chunk (tile or strip) in the image is sparse, i.e. has a byte count of 0
+    fn has_sparse_chunks(&mut self) -> TiffResult<bool> {
+        let (chunks_across, chunks_down): (u32, u32) = self.chunk_grid()?;
+        for chunk_index in 0..chunks_across * chunks_down {
+            if self.chunk_byte_count(chunk_index)? == 0 {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Decode a single chunk (tile or strip) to an [`ndarray::Array`] of shape
+    /// (band, height, width), along with the chunk's position within the image
+    fn read_chunk_ndarray<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        chunk_index: u32,
+    ) -> TiffResult<(TilePos, Array3<T>)> {
+        // Chunks are only decoded for pixel-interleaved (chunky) data
+        if self
+            .decoder
+            .find_tag_unsigned::<u16>(Tag::PlanarConfiguration)?
+            == Some(2)
+        {
+            return Err(TiffError::UnsupportedError(
+                TiffUnsupportedError::UnsupportedPlanarConfig(Some(PlanarConfiguration::Planar)),
+            ));
+        }
+        let num_bands: usize = self.num_samples()?;
+
+        // Get position of chunk within the image
+        let tile_pos: TilePos = self.chunk_position(chunk_index)?;
+        let (width, height): (u32, u32) = (tile_pos.width, tile_pos.height);
+
+        // Fill sparse chunks (with a byte count of zero) with NoData, or zero if unset
+        if self.chunk_byte_count(chunk_index)? == 0 {
+            let fill_value: T = match self.nodata()? {
+                Some(nodata) => T::from_f64(nodata).unwrap_or(T::from_u8(0).unwrap()),
+                None => T::from_u8(0).unwrap(),
+            };
+            let array_data: Array3<T> =
+                Array3::from_elem((num_bands, height as usize, width as usize), fill_value);
+            return Ok((tile_pos, array_data));
+        }
+
+        // Get chunk pixel data
+        self.check_compression()?;
+        self.check_sample_format()?;
+        let decode_result = self.decoder.read_chunk(chunk_index)?;
+        let chunk_data: Vec<T> = decoding_result_to_vec(decode_result);
+
+        // Put chunk pixel data into an ndarray, moving the band axis first
+        let array_data: Array3<T> =
+            Array3::from_shape_vec((height as usize, width as usize, num_bands), chunk_data)
+                .map_err(|_| TiffFormatError::InconsistentSizesEncountered)?
+                .permuted_axes([2, 0, 1]);
+
+        Ok((tile_pos, array_data))
+    }
+
+    /// Decode a single chunk (tile or strip) like [`read_chunk_ndarray`](Self::read_chunk_ndarray),
+    /// but flipped and/or transposed according to the image's orientation, along with the
+    /// chunk's position within the visual image
+    fn read_oriented_chunk<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+        chunk_index: u32,
+    ) -> TiffResult<(TilePos, Array3<T>)> {
+        let orientation: u16 = self.orientation()?;
+        let dimensions: (u32, u32) = self.oriented_dimensions()?;
+        let (tile_pos, array_data): (TilePos, Array3<T>) = self.read_chunk_ndarray(chunk_index)?;
+
+        Ok((
+            oriented_tile_pos(tile_pos, orientation, dimensions),
+            apply_orientation(array_data, orientation),
+        ))
+    }
+
+    /// Affine transformation for 2D matrix extracted from TIFF tag metadata, used to transform
+    /// image pixel (row, col) coordinates to and from geographic/projected (x, y) coordinates.
+    ///
+    /// ```text
+    /// | x' |   | a b c | | x |
+    /// | y' | = | d e f | | y |
+    /// | 1  |   | 0 0 1 | | 1 |
+    /// ```
+    ///
+    /// where (`x'` and `y'`) are world coordinates, and (`x`, `y`) are the pixel's
+    /// image coordinates. Letters a to f represent:
+    ///
+    /// - `a` - width of a pixel (x-resolution)
+    /// - `b` - row rotation (typically zero)
+    /// - `c` - x-coordinate of the *center* of the upper-left pixel (x-origin)
+    /// - `d` - column rotation (typically zero)
+    /// - `e` - height of a pixel (y-resolution, typically negative)
+    /// - `f` - y-coordinate of the *center* of the upper-left pixel (y-origin)
+    ///
+    /// If the ModelTransformationTag is present, the coefficients (including any rotation
+    /// or shear, or a positive `e` for south-up images) are taken from its 4x4 matrix.
+    /// Otherwise, they are derived from the ModelPixelScaleTag and ModelTiepointTag without
+    /// rotation. If the ModelTiepointTag is absent, the upper-left pixel is assumed to be at
+    /// the origin (0, 0), as relied upon by some minimally-tagged GeoTIFF files.
+    ///
+    /// The ModelPixelScaleTag holds the pixel size as positive values, with the y-scale
+    /// negated to get a north-up image. Some writers store the y-scale as a negative value
+    /// already, so like GDAL, its sign is ignored rather than negated again, which would
+    /// make the image upside-down. The tiepoint may also tie any raster position (not just
+    /// the upper-left corner) to world coordinates, from which the origin is derived.
+    ///
+    /// Note that this means images georeferenced with the ModelPixelScaleTag are always
+    /// treated as north-up (negative `e`), as done by GDAL unless its
+    /// `GTIFF_HONOUR_NEGATIVE_SCALEY` option is set. South-up images are only supported
+    /// when stored with a ModelTransformationTag.
+    ///
+    /// References:
+    /// - <https://docs.ogc.org/is/19-008r4/19-008r4.html#_coordinate_transformations>
+    pub fn transform(&mut self) -> TiffResult<AffineTransform<f64>> {
+        if let Some(transform) = self.metadata.transform {
+            return Ok(transform);
+        }
+        let transform: AffineTransform<f64> = self.read_transform()?;
+        self.metadata.transform = Some(transform);
+
+        Ok(transform)
+    }
+
+    /// Read the affine [`transform`](Self::transform) from the GeoTIFF tags
+    fn read_transform(&mut self) -> TiffResult<AffineTransform<f64>> {
+        // Get full model transformation, stored as a row-major 4x4 matrix
+        if let Some(value) = self.decoder.find_tag(Tag::ModelTransformationTag)? {
+            let model_transformation: Vec<f64> = value.into_f64_vec()?;
+            let Some(&[a, b, _, c, d, e, _, f]) = model_transformation.get(0..8) else {
+                return Err(TiffError::FormatError(TiffFormatError::InvalidTag));
+            };
+            return Ok(AffineTransform::new(a, b, c, d, e, f));
+        }
+
+        // Get pixel size in x and y direction
+        let Some(pixel_scale) = self.pixel_scale()? else {
+            return Err(TiffError::FormatError(
+                TiffFormatError::RequiredTagNotFound(Tag::ModelPixelScaleTag),
+            ));
+        };
+        let [x_scale, y_scale, _z_scale] = pixel_scale[0..3] else {
+            return Err(TiffError::FormatError(TiffFormatError::InvalidTag));
+        };
+
+        // Pixel height is negative for north-up images, regardless of the stored sign
+        let y_res: f64 = -y_scale.abs();
+
+        // Get x and y coordinates of upper left pixel, defaulting to (0, 0) if not tagged,
+        // from the raster position (i, j) and world coordinates (x, y) of the tiepoint
+        let (x_origin, y_origin): (f64, f64) = match self.tiepoint()? {
+            Some(tie_points) => {
+                let [i, j, _k, x, y, _z] = tie_points[0..6] else {
+                    return Err(TiffError::FormatError(TiffFormatError::InvalidTag));
+                };
+                (x - i * x_scale, y - j * y_res)
+            }
+            None => (0.0, 0.0),
+        };
+
+        // Create affine transformation matrix, without rotation
+        let transform = AffineTransform::new(x_scale, 0.0, x_origin, 0.0, y_res, y_origin);
+
+        Ok(transform)
+    }
+
+    /// Values of the ModelPixelScaleTag (33550), i.e. the pixel size in the x, y and z
+    /// directions, or `None` if the tag is absent
+    fn pixel_scale(&mut self) -> TiffResult<Option<Vec<f64>>> {
+        if let Some(pixel_scale) = &self.metadata.pixel_scale {
+            return Ok(pixel_scale.clone());
+        }
+        let pixel_scale: Option<Vec<f64>> = match self.decoder.find_tag(Tag::ModelPixelScaleTag)? {
+            Some(value) => Some(value.into_f64_vec()?),
+            None => None,
+        };
+        self.metadata.pixel_scale = Some(pixel_scale.clone());
+
+        Ok(pixel_scale)
+    }
+
+    /// Values of the ModelTiepointTag (33922), i.e. one or more (i, j, k, x, y, z) tuples
+    /// tying raster positions to world coordinates, or `None` if the tag is absent
+    fn tiepoint(&mut self) -> TiffResult<Option<Vec<f64>>> {
+        if let Some(tiepoint) = &self.metadata.tiepoint {
+            return Ok(tiepoint.clone());
+        }
+        let tiepoint: Option<Vec<f64>> = match self.decoder.find_tag(Tag::ModelTiepointTag)? {
+            Some(value) => Some(value.into_f64_vec()?),
+            None => None,
+        };
+        self.metadata.tiepoint = Some(tiepoint.clone());
+
+        Ok(tiepoint)
+    }
+
+    /// Whether the image is georeferenced, i.e. has either a ModelPixelScaleTag (33550) and
+    /// ModelTiepointTag (33922), or a ModelTransformationTag (34264), from which the
+    /// [`transform`](Self::transform) to coordinates can be derived.
+    ///
+    /// Plain (non-Geo) TIFF files have none of these tags, so their pixels can still be
+    /// decoded, but methods that return coordinates (e.g. [`xy_coords`](Self::xy_coords))
+    /// should be skipped.
+    pub fn is_georeferenced(&mut self) -> TiffResult<bool> {
+        let has_pixel_scale: bool = self.pixel_scale()?.is_some();
+        let has_tie_points: bool = self.tiepoint()?.is_some();
+        let has_model_transformation: bool = self
+            .decoder
+            .find_tag(Tag::ModelTransformationTag)?
+            .is_some();
+
+        Ok((has_pixel_scale && has_tie_points) || has_model_transformation)
+    }
+
+    /// Bounding box of the full image as `[min_x, min_y, max_x, max_y]`, in the file's own
+    /// coordinate reference system.
+    ///
+    /// The bounds are taken at the outer edges of the corner pixels (not their centers),
+    /// by applying the affine [`transform`](Self::transform) to the four corners of the
+    /// image, so that they hold regardless of the sign of the x and y resolution, and
+    /// enclose the footprint of rotated images.
+    pub fn bounds(&mut self) -> TiffResult<[f64; 4]> {
+        let transform: AffineTransform<f64> = self.transform()?;
+        let (width, height): (u32, u32) = self.dimensions()?;
+        let (width, height): (f64, f64) = (width as f64, height as f64);
+
+        let corners: [Coord<f64>; 4] = [(0.0, 0.0), (width, 0.0), (0.0, height), (width, height)]
+            .map(|(col, row)| transform.apply(Coord { x: col, y: row }));
+        let bounds: [f64; 4] = corners.iter().fold(
+            [
+                f64::INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::NEG_INFINITY,
+            ],
+            |[min_x, min_y, max_x, max_y], &Coord { x, y }| {
+                [min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)]
+            },
+        );
+
+        Ok(bounds)
+    }
+
+    /// Pixel size in the x and y direction, i.e. the absolute values of the `a` and `e`
+    /// coefficients of the affine [`transform`](Self::transform).
+    ///
+    /// The resolution is in the units of the coordinate reference system, which are
+    /// degrees for geographic coordinates (see [`is_geographic`](Self::is_geographic)), and
+    /// usually meters for projected coordinates.
+    pub fn resolution(&mut self) -> TiffResult<(f64, f64)> {
+        let transform = self.transform()?;
+
+        Ok((transform.a().abs(), transform.e().abs()))
+    }
+
+    /// Whether the coordinate reference system is geographic, i.e. coordinates are
+    /// longitude/latitude in degrees, as indicated by the GTModelTypeGeoKey (1024) in the
+    /// GeoKeyDirectoryTag (34735). Returns `false` for projected coordinate systems, or if
+    /// the GeoKey is absent.
+    pub fn is_geographic(&mut self) -> TiffResult<bool> {
+        let model_type: Option<u16> = self.geo_key_short(GT_MODEL_TYPE_GEO_KEY)?;
+
+        Ok(model_type == Some(MODEL_TYPE_GEOGRAPHIC))
+    }
+
+    /// EPSG code of the coordinate reference system (e.g. 32633 for "WGS 84 / UTM zone
+    /// 33N"), taken from the ProjectedCSTypeGeoKey (3072), or from the GeographicTypeGeoKey
+    /// (2048) for geographic coordinate systems. Returns `None` if neither GeoKey is
+    /// present, or if the coordinate system is user-defined (see
+    /// [`crs_citation`](Self::crs_citation) for a description of those instead).
+    pub fn epsg_code(&mut self) -> TiffResult<Option<u32>> {
+        if let Some(epsg_code) = self.metadata.epsg_code {
+            return Ok(epsg_code);
+        }
+
+        let mut epsg_code: Option<u32> = None;
+        for key_id in CRS_TYPE_GEO_KEYS {
+            match self.geo_key_short(key_id)? {
+                Some(0) | Some(USER_DEFINED) | None => continue,
+                Some(code) => {
+                    epsg_code = Some(code as u32);
+                    break;
+                }
+            }
+        }
+        self.metadata.epsg_code = Some(epsg_code);
+
+        Ok(epsg_code)
+    }
+
+    /// Human-readable description of the coordinate reference system (e.g. "WGS 84 / UTM
+    /// zone 33N"), which can be used to identify user-defined coordinate systems that have
+    /// no EPSG code. Returns `None` if the file has no citation GeoKeys.
+    ///
+    /// The description is resolved from the GeoAsciiParamsTag (34737), taking the first of
+    /// the GTCitationGeoKey (1026), PCSCitationGeoKey (3073) and GeogCitationGeoKey (2049)
+    /// in the GeoKeyDirectoryTag (34735) that is present.
+    pub fn crs_citation(&mut self) -> TiffResult<Option<String>> {
+        for key_id in CITATION_GEO_KEYS {
+            if let Some(citation) = self.geo_key_ascii(key_id)? {
+                return Ok(Some(citation));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Value of a SHORT GeoKey stored directly in the GeoKeyDirectoryTag (34735), or `None`
+    /// if the tag or the GeoKey is absent.
+    ///
+    /// The GeoKeyDirectoryTag is a list of SHORT values, starting with a header of
+    /// (version, revision, minor revision, number of keys), followed by an entry of
+    /// (key ID, TIFF tag location, count, value) for each GeoKey.
+    fn geo_key_short(&mut self, key_id: u16) -> TiffResult<Option<u16>> {
+        let Some(geo_key_directory) = self
+            .decoder
+            .find_tag_unsigned_vec::<u16>(Tag::GeoKeyDirectoryTag)?
+        else {
+            return Ok(None);
+        };
+
+        // Values of SHORT GeoKeys are stored in the entry itself, with a location of 0
+        let value: Option<u16> = geo_key_directory
+            .chunks_exact(4)
+            .skip(1)
+            .find(|entry| entry[0] == key_id && entry[1] == 0)
+            .map(|entry| entry[3]);
+
+        Ok(value)
+    }
+
+    /// Value of an ASCII GeoKey stored in the GeoAsciiParamsTag (34737), or `None` if the
+    /// tags or the GeoKey are absent (or the GeoKey is empty).
+    ///
+    /// ASCII GeoKeys reference a substring of the GeoAsciiParamsTag by (count, offset) in
+    /// their GeoKeyDirectoryTag entry, where each substring is terminated by a `|`.
+    fn geo_key_ascii(&mut self, key_id: u16) -> TiffResult<Option<String>> {
+        let Some(geo_key_directory) = self
+            .decoder
+            .find_tag_unsigned_vec::<u16>(Tag::GeoKeyDirectoryTag)?
+        else {
+            return Ok(None);
+        };
+        let Some(entry) = geo_key_directory
+            .chunks_exact(4)
+            .skip(1)
+            .find(|entry| entry[0] == key_id && entry[1] == Tag::GeoAsciiParamsTag.to_u16())
+        else {
+            return Ok(None);
+        };
+        let Some(ascii_params) = self.decoder.find_tag(Tag::GeoAsciiParamsTag)? else {
+            return Ok(None);
+        };
+
+        let ascii_params: String = ascii_params.into_string()?;
+        let (count, offset): (usize, usize) = (entry[2] as usize, entry[3] as usize);
+        let value: &str = ascii_params.get(offset..offset + count).ok_or_else(|| {
+            TiffError::FormatError(TiffFormatError::Format(format!(
+                "GeoKey {key_id} (count={count}, offset={offset}) is out of bounds of \
+                 GeoAsciiParamsTag with length {}",
+                ascii_params.len()
+            )))
+        })?;
+        let value: &str = value.trim_end_matches(['|', char::from(0)]).trim();
+
+        Ok((!value.is_empty()).then(|| value.to_string()))
+    }
+
+    /// Rational polynomial coefficients (RPCs) from the RPCCoefficientTag (50844), used to
+    /// orthorectify satellite imagery. Returns `None` if the tag is absent, or an error if
+    /// it does not hold the 92 values of an RPC model.
+    ///
+    /// References:
+    /// - <http://geotiff.maptools.org/rpc_prop.html>
+    pub fn rpc(&mut self) -> TiffResult<Option<RpcCoefficients>> {
+        let Some(value) = self.decoder.find_tag(Tag::from_u16_exhaustive(50844))? else {
+            return Ok(None);
+        };
+        let values: Vec<f64> = value.into_f64_vec()?;
+        if values.len() != 92 {
+            return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+                "RPCCoefficientTag has {} values, expected 92",
+                values.len()
+            ))));
+        }
+
+        let polynomial =
+            |start: usize| -> [f64; 20] { values[start..start + 20].try_into().unwrap() };
+        let rpc = RpcCoefficients {
+            err_bias: values[0],
+            err_rand: values[1],
+            line_off: values[2],
+            samp_off: values[3],
+            lat_off: values[4],
+            long_off: values[5],
+            height_off: values[6],
+            line_scale: values[7],
+            samp_scale: values[8],
+            lat_scale: values[9],
+            long_scale: values[10],
+            height_scale: values[11],
+            line_num: polynomial(12),
+            line_den: polynomial(32),
+            samp_num: polynomial(52),
+            samp_den: polynomial(72),
+        };
+
+        Ok(Some(rpc))
+    }
+
+    /// NoData value extracted from the GDAL_NODATA TIFF tag (42113), i.e. the pixel value
+    /// used to indicate missing data. Returns `None` if the tag is absent.
+    pub fn nodata(&mut self) -> TiffResult<Option<f64>> {
+        if let Some(nodata) = self.metadata.nodata {
+            return Ok(nodata);
+        }
+
+        let nodata: Option<f64> = match self.decoder.find_tag(Tag::GdalNodata)? {
+            Some(value) => {
+                let nodata_str: String = value.into_string()?;
+                let nodata_str: &str = nodata_str.trim_matches(char::from(0)).trim();
+                let nodata: f64 = nodata_str.parse().map_err(|_| {
+                    TiffError::FormatError(TiffFormatError::Format(format!(
+                        "Cannot parse GDAL_NODATA value: {nodata_str}"
+                    )))
+                })?;
+                Some(nodata)
+            }
+            None => None,
+        };
+        self.metadata.nodata = Some(nodata);
+
+        Ok(nodata)
+    }
+
+    /// Provenance of the file, i.e. when and by what software it was created, from the
+    /// DateTime (306) and Software (305) TIFF tags. Either is `None` if its tag is absent.
+    pub fn provenance(&mut self) -> TiffResult<Provenance> {
+        Ok(Provenance {
+            date_time: self.ascii_tag(Tag::DateTime)?,
+            software: self.ascii_tag(Tag::Software)?,
+        })
+    }
+
+    /// Value of an ASCII TIFF tag, without any trailing NUL characters, or `None` if the
+    /// tag is absent
+    fn ascii_tag(&mut self, tag: Tag) -> TiffResult<Option<String>> {
+        let value: Option<String> = match self.decoder.find_tag(tag)? {
+            Some(value) => Some(value.into_string()?.trim_end_matches('\0').to_string()),
+            None => None,
+        };
+
+        Ok(value)
+    }
+
+    /// Metadata items stored as XML in the GDAL_METADATA TIFF tag (42112), split into
+    /// dataset-level items and per-band items (e.g. `DESCRIPTION`, `UNITTYPE`, `SCALE`,
+    /// `OFFSET` or `STATISTICS_MAXIMUM`). Items in non-default metadata domains are skipped.
+    ///
+    /// The number of bands always matches the number of samples per pixel, with bands
+    /// that have no metadata items being empty. Returns empty metadata if the tag is absent.
+    pub fn gdal_metadata(&mut self) -> TiffResult<GdalMetadata> {
+        let num_bands: usize = self.samples_per_pixel()?;
+        let gdal_metadata: GdalMetadata =
+            match self.decoder.find_tag(Tag::from_u16_exhaustive(42112))? {
+                Some(value) => parse_gdal_metadata(&value.into_string()?, num_bands),
+                None => GdalMetadata {
+                    dataset: HashMap::new(),
+                    bands: vec![HashMap::new(); num_bands],
+                },
+            };
+
+        Ok(gdal_metadata)
+    }
+
+    /// Scale and offset of each band, taken from the per-band `SCALE` and `OFFSET` items in
+    /// the GDAL_METADATA TIFF tag, used to convert stored pixel values to physical values
+    /// as `value * scale + offset`. Defaults to a scale of 1 and an offset of 0 for bands
+    /// where the items are absent (or cannot be parsed).
+    pub fn scale_offset(&mut self) -> TiffResult<Vec<(f64, f64)>> {
+        let scale_offset: Vec<(f64, f64)> = self
+            .gdal_metadata()?
+            .bands
+            .iter()
+            .map(|band| {
+                let scale: Option<f64> = band.get("SCALE").and_then(|v| v.trim().parse().ok());
+                let offset: Option<f64> = band.get("OFFSET").and_then(|v| v.trim().parse().ok());
+                (scale.unwrap_or(1.0), offset.unwrap_or(0.0))
+            })
+            .collect();
+
+        Ok(scale_offset)
+    }
+
+    /// Name of each band, taken from the per-band `DESCRIPTION` items in the GDAL_METADATA
+    /// TIFF tag. Bands without a description are `None`, so that the number of names
+    /// always matches the number of bands.
+    pub fn band_names(&mut self) -> TiffResult<Vec<Option<String>>> {
+        let band_names: Vec<Option<String>> = self
+            .gdal_metadata()?
+            .bands
+            .into_iter()
+            .map(|mut band| band.remove("DESCRIPTION"))
+            .collect();
+
+        Ok(band_names)
+    }
+
+    /// Vertical (z-axis) scale and origin extracted from TIFF tag metadata, used to
+    /// convert raw pixel values to elevation units, e.g. for Digital Elevation Models.
+    ///
+    /// Returns `Some((z_scale, z_origin))`, where `z_scale` is the third value of the
+    /// ModelPixelScaleTag, and `z_origin` is the sixth value of the ModelTiepointTag
+    /// (defaults to 0.0 if absent). Returns `None` if the z-scale is zero or absent.
+    pub fn z_transform(&mut self) -> TiffResult<Option<(f64, f64)>> {
+        // Get pixel size in z direction
+        let z_scale: f64 = match self.pixel_scale()? {
+            Some(pixel_scale) => match pixel_scale.get(2) {
+                Some(z_scale) if *z_scale != 0.0 => *z_scale,
+                _ => return Ok(None),
+            },
+            None => return Ok(None),
+        };
+
+        // Get z coordinate of upper left pixel
+        let z_origin: f64 = match self.tiepoint()? {
+            Some(tie_points) => tie_points.get(5).copied().unwrap_or(0.0),
+            None => 0.0,
+        };
+
+        Ok(Some((z_scale, z_origin)))
+    }
+
+    /// Convert an image pixel (row, col) position to geographic/projected (x, y)
+    /// coordinates using the affine [`transform`](Self::transform).
+    ///
+    /// Note that integer (row, col) values refer to the upper-left corner of a pixel, so
+    /// add 0.5 to each to get the coordinates of the pixel's center.
+    pub fn pixel_to_world(&mut self, row: f64, col: f64) -> TiffResult<(f64, f64)> {
+        let transform = self.transform()?;
+        let Coord { x, y } = transform.apply(Coord { x: col, y: row });
+
+        Ok((x, y))
+    }
+
+    /// Convert geographic/projected (x, y) coordinates to an image pixel (row, col)
+    /// position using the inverse of the affine [`transform`](Self::transform).
+    ///
+    /// Returns an error if the affine transform is degenerate (i.e. not invertible).
+    pub fn world_to_pixel(&mut self, x: f64, y: f64) -> TiffResult<(f64, f64)> {
+        let inverse_transform = self.transform()?.inverse().ok_or_else(|| {
+            TiffError::FormatError(TiffFormatError::Format(String::from(
+                "Affine transform is not invertible",
+            )))
+        })?;
+        let Coord { x: col, y: row } = inverse_transform.apply(Coord { x, y });
+
+        Ok((row, col))
+    }
+
+    /// Decode GeoTIFF image to a [`GeoArray3`], i.e. an [`ndarray::Array`] of shape
+    /// (band, height, width) along with its x and y coordinates, affine transform and EPSG
+    /// code, similar to an xarray.DataArray in Python.
+    ///
+    /// Returns an error for rotated or sheared images, which have no 1D coordinates (see
+    /// [`xy_coords`](Self::xy_coords)).
+    pub fn read_geo_array<T: FromPrimitive + Clone + 'static>(
+        &mut self,
+    ) -> TiffResult<GeoArray3<T>> {
+        let (x, y): (Array1<f64>, Array1<f64>) = self.xy_coords()?;
+
+        Ok(GeoArray3 {
+            data: self.ndarray()?,
+            x,
+            y,
+            transform: self.transform()?,
+            epsg: self.epsg_code()?,
+        })
+    }
+
+    /// Get list of x and y coordinates
+    ///
+    /// Returns an error for rotated or sheared images, whose x and y coordinates vary along
+    /// both image axes, use [`xy_coords_2d`](Self::xy_coords_2d) for those instead.
+    pub fn xy_coords(&mut self) -> TiffResult<(Array1<f64>, Array1<f64>)> {
+        let transform = self.transform()?; // affine transformation matrix
+        if transform.b() != 0.0 || transform.d() != 0.0 {
+            return Err(TiffError::FormatError(TiffFormatError::Format(
+                String::from(
+                    "Cannot get 1D coordinates of a rotated image, use xy_coords_2d instead",
+                ),
+            )));
+        }
+
+        // Get spatial resolution in x and y dimensions
+        let x_res: &f64 = &transform.a();
+        let y_res: &f64 = &transform.e();
+
+        // Get xy coordinate of the center of the top left pixel
+        let x_origin: &f64 = &(transform.xoff() + x_res / 2.0);
+        let y_origin: &f64 = &(transform.yoff() + y_res / 2.0);
+
+        // Get number of pixels along the x and y dimensions
+        let (x_pixels, y_pixels): (u32, u32) = self.dimensions()?;
+
+        // Get xy coordinate of the center of the bottom right pixel
+        let x_end: f64 = x_origin + x_res * x_pixels as f64;
+        let y_end: f64 = y_origin + y_res * y_pixels as f64;
+
+        // Get array of x-coordinates and y-coordinates
+        let x_coords = Array::range(x_origin.to_owned(), x_end, x_res.to_owned());
+        let y_coords = Array::range(y_origin.to_owned(), y_end, y_res.to_owned());
+
+        Ok((x_coords, y_coords))
+    }
+
+    /// Get 2D grids of the x and y coordinates of every pixel center, each of shape
+    /// (height, width), by applying the affine [`transform`](Self::transform) to every
+    /// pixel. Unlike [`xy_coords`](Self::xy_coords), this works for images that are rotated
+    /// or sheared (e.g. with a ModelTransformationTag), but uses more memory.
+    pub fn xy_coords_2d(&mut self) -> TiffResult<(Array2<f64>, Array2<f64>)> {
+        let transform = self.transform()?;
+        let (width, height): (u32, u32) = self.dimensions()?;
+        let shape: (usize, usize) = (height as usize, width as usize);
+
+        let centers: Array2<Coord<f64>> = Array2::from_shape_fn(shape, |(row, col)| {
+            transform.apply(Coord {
+                x: col as f64 + 0.5,
+                y: row as f64 + 0.5,
+            })
+        });
+        let x_coords: Array2<f64> = centers.mapv(|center| center.x);
+        let y_coords: Array2<f64> = centers.mapv(|center| center.y);
+
+        Ok((x_coords, y_coords))
+    }
+
+    /// Get list of x and y coordinates, both sorted in ascending order.
+    ///
+    /// Unlike [`xy_coords`](Self::xy_coords), which follows the pixel order of the image
+    /// (so that y-coordinates are usually descending, as the pixel height is negative),
+    /// the coordinates are reversed where needed. Note that the caller must then also flip
+    /// the decoded array along the height (or width) axis to match, e.g. using
+    /// `array.slice(s![.., ..;-1, ..])`, when the y (or x) resolution is negative.
+    pub fn xy_coords_sorted(&mut self) -> TiffResult<(Array1<f64>, Array1<f64>)> {
+        let (mut x_coords, mut y_coords) = self.xy_coords()?;
+
+        let transform = self.transform()?;
+        if transform.a() < 0.0 {
+            x_coords.invert_axis(Axis(0));
+        }
+        if transform.e() < 0.0 {
+            y_coords.invert_axis(Axis(0));
+        }
+
+        Ok((x_coords, y_coords))
+    }
+}
+
+impl<'a> CogReader<Cursor<&'a [u8]>> {
+    /// Create a new GeoTIFF decoder that decodes from a borrowed byte slice (e.g. a buffer
+    /// owned by the caller), without copying it into an owned buffer such as [`Bytes`]
+    pub fn from_slice(data: &'a [u8]) -> TiffResult<Self> {
+        Self::new(Cursor::new(data))
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl CogReader<Cursor<Mmap>> {
+    /// Create a new GeoTIFF decoder that decodes from a memory-mapped local file
+    ///
+    /// Instead of reading the whole file into memory, the operating system pages in only
+    /// the parts of the file (e.g. headers and tiles) that are actually decoded. Requires
+    /// the `mmap` feature.
+    pub fn from_path_mmap(path: &std::path::Path) -> TiffResult<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: The file must not be modified (e.g. truncated) by another process while
+        // it is mapped, which is the usual caveat for reading files via mmap.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        Self::new(Cursor::new(mmap))
+    }
+}
+
+/// Pool of [`CogReader`]s over the same in-memory GeoTIFF file, for serving many concurrent
+/// reads (e.g. tile requests in a tile server) without re-parsing the file for each one.
+///
+/// The file's bytes are held once in a reference-counted [`Bytes`] buffer that is shared
+/// (not copied) by every reader. Readers are handed out by [`get`](Self::get) and returned
+/// to the pool when the [`PooledCogReader`] guard is dropped, so the TIFF header and Image
+/// File Directories (IFDs) are only parsed when the pool has no idle reader, i.e. at most
+/// once per concurrently used reader rather than once per read.
+///
+/// The pool is [`Send`] and [`Sync`], so it can be shared between threads (e.g. via an
+/// [`std::sync::Arc`] or a reference in a scoped thread). Each [`PooledCogReader`] is used
+/// exclusively by the thread holding it, so reads from different threads never block each
+/// other, apart from briefly locking the pool to take or return a reader. Returned readers
+/// are reset to the first image of the file.
+pub struct CogReaderPool {
+    /// Shared buffer holding the bytes of the GeoTIFF file
+    data: Bytes,
+    /// Readers that are not currently in use
+    idle: Mutex<Vec<CogReader<Cursor<Bytes>>>>,
+}
+
+impl CogReaderPool {
+    /// Create a new pool of GeoTIFF decoders that decode from a shared in-memory buffer
+    ///
+    /// The file is parsed once up front, so that an invalid file is reported here rather
+    /// than on the first call to [`get`](Self::get).
+    pub fn new(data: Bytes) -> TiffResult<Self> {
+        let reader = CogReader::new(Cursor::new(data.clone()))?;
+
+        Ok(Self {
+            data,
+            idle: Mutex::new(vec![reader]),
+        })
+    }
+
+    /// Take a reader from the pool, creating a new one if all readers are in use. The
+    /// reader is returned to the pool when the guard is dropped.
+    pub fn get(&self) -> TiffResult<PooledCogReader<'_>> {
+        let idle_reader: Option<CogReader<Cursor<Bytes>>> =
+            self.idle.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        let reader = match idle_reader {
+            Some(reader) => reader,
+            None => CogReader::new(Cursor::new(self.data.clone()))?,
+        };
+
+        Ok(PooledCogReader {
+            reader: Some(reader),
+            pool: self,
+        })
+    }
+
+    /// Number of idle readers in the pool, i.e. readers that have been created and are not
+    /// currently in use
+    pub fn num_idle(&self) -> usize {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+/// [`CogReader`] borrowed from a [`CogReaderPool`], which dereferences to the reader and
+/// returns it to the pool when dropped
+pub struct PooledCogReader<'a> {
+    /// Reader in use, which is only `None` while being returned to the pool
+    reader: Option<CogReader<Cursor<Bytes>>>,
+    /// Pool that the reader is returned to
+    pool: &'a CogReaderPool,
+}
+
+impl Deref for PooledCogReader<'_> {
+    type Target = CogReader<Cursor<Bytes>>;
+
+    fn deref(&self) -> &Self::Target {
+        self.reader.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledCogReader<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.reader.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledCogReader<'_> {
+    fn drop(&mut self) {
+        if let Some(mut reader) = self.reader.take() {
+            // Reset to the first image, and discard readers that cannot be reset
+            if reader.ifd_index == 0 || reader.seek_to_ifd(0).is_ok() {
+                let mut idle = self.pool.idle.lock().unwrap_or_else(|e| e.into_inner());
+                idle.push(reader);
+            }
+        }
+    }
+}
+
+/// Layout of a GeoTIFF image's tiles (or strips) as the chunks of a Zarr array with shape
+/// (height, width, band), as returned by [`CogReader::to_zarr_chunk_spec`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZarrChunkSpec {
+    /// Shape of the array as (height, width, band)
+    pub shape: [usize; 3],
+    /// Shape of each chunk (tile or strip) as (height, width, band)
+    pub chunk_shape: [usize; 3],
+    /// Data type as a NumPy array-protocol type string, e.g. "<f4" for little-endian
+    /// float32 or "|u1" for uint8
+    pub dtype: String,
+    /// Value of pixels in chunks that are not stored, from the GDAL_NODATA TIFF tag
+    pub fill_value: Option<f64>,
+    /// Compression method of every chunk
+    pub compression: CompressionMethod,
+    /// Predictor applied to every chunk before compression
+    pub predictor: Predictor,
+    /// Location of every chunk that is stored in the file, in row-major order
+    pub chunks: Vec<ZarrChunkRef>,
+}
+
+/// Location of a chunk (tile or strip) of a Zarr array within a GeoTIFF file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZarrChunkRef {
+    /// Index of the chunk in the chunk grid, as (row, column, band)
+    pub index: [usize; 3],
+    /// Byte offset of the chunk from the start of the file
+    pub offset: u64,
+    /// Number of bytes of the (compressed) chunk
+    pub length: u64,
+}
+
+/// Rational polynomial coefficients (RPCs) of a satellite image, as stored in the
+/// RPCCoefficientTag (50844), which map geographic coordinates (longitude, latitude,
+/// height) to image (line, sample) coordinates
+#[derive(Clone, Debug, PartialEq)]
+pub struct RpcCoefficients {
+    /// Bias error in meters, or -1 if unknown
+    pub err_bias: f64,
+    /// Random error in meters, or -1 if unknown
+    pub err_rand: f64,
+    /// Line (row) offset in pixels
+    pub line_off: f64,
+    /// Sample (column) offset in pixels
+    pub samp_off: f64,
+    /// Latitude offset in degrees
+    pub lat_off: f64,
+    /// Longitude offset in degrees
+    pub long_off: f64,
+    /// Height offset in meters
+    pub height_off: f64,
+    /// Line (row) scale in pixels
+    pub line_scale: f64,
+    /// Sample (column) scale in pixels
+    pub samp_scale: f64,
+    /// Latitude scale in degrees
+    pub lat_scale: f64,
+    /// Longitude scale in degrees
+    pub long_scale: f64,
+    /// Height scale in meters
+    pub height_scale: f64,
+    /// Coefficients of the numerator polynomial for the line (row)
+    pub line_num: [f64; 20],
+    /// Coefficients of the denominator polynomial for the line (row)
+    pub line_den: [f64; 20],
+    /// Coefficients of the numerator polynomial for the sample (column)
+    pub samp_num: [f64; 20],
+    /// Coefficients of the denominator polynomial for the sample (column)
+    pub samp_den: [f64; 20],
+}
+
+/// GDAL structural metadata from the ghost area of a COG, as returned by
+/// [`CogReader::ghost_header`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GhostHeader {
+    /// Whether all IFDs are stored before the tile data (`LAYOUT=IFDS_BEFORE_DATA`), so
+    /// that the headers can be read with a single range request from the start of the file
+    pub ifds_before_data: bool,
+    /// Whether tiles are stored in row-major order, from the smallest overview to the full
+    /// resolution image (`BLOCK_ORDER=ROW_MAJOR`)
+    pub block_order_row_major: bool,
+    /// Whether each tile is preceded by its byte count as a 4-byte unsigned integer
+    /// (`BLOCK_LEADER=SIZE_AS_UINT4`)
+    pub block_leader_size_as_uint4: bool,
+    /// Whether each tile is followed by a repeat of its last 4 bytes
+    /// (`BLOCK_TRAILER=LAST_4_BYTES_REPEATED`), to detect edits that broke the layout
+    pub block_trailer_last_4_bytes_repeated: bool,
+    /// Whether each tile of the mask is stored right after the corresponding imagery tile
+    /// (`MASK_INTERLEAVED_WITH_IMAGERY=YES`)
+    pub mask_interleaved_with_imagery: bool,
+    /// Whether the file was modified after being written, so that the layout above may no
+    /// longer hold (`KNOWN_INCOMPATIBLE_EDITION=YES`)
+    pub known_incompatible_edition: bool,
+    /// All items of the structural metadata as (key, value) pairs, in file order
+    pub items: Vec<(String, String)>,
+}
+
+/// Outcome of [`CogReader::verify_tile_checksums`], holding the position of each tile that
+/// failed verification along with the reason. An empty list means that all tiles are intact.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TileChecksums {
+    /// The file carries per-tile checksums (GDAL block trailers), and every tile was checked
+    /// against its checksum as well as decoded
+    Verified(Vec<(TilePos, String)>),
+    /// The file has no per-tile checksums, so tiles were only checked by decoding them and
+    /// comparing byte counts
+    NotAvailable(Vec<(TilePos, String)>),
+}
+
+/// Data type of the samples of a decoded image, as returned by [`CogReader::read_raw`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataType {
+    /// 8-bit unsigned integer
+    UInt8,
+    /// 16-bit unsigned integer
+    UInt16,
+    /// 32-bit unsigned integer
+    UInt32,
+    /// 64-bit unsigned integer
+    UInt64,
+    /// 8-bit signed integer
+    Int8,
+    /// 16-bit signed integer
+    Int16,
+    /// 32-bit signed integer
+    Int32,
+    /// 64-bit signed integer
+    Int64,
+    /// 32-bit floating point
+    Float32,
+    /// 64-bit floating point
+    Float64,
+}
+
+impl DataType {
+    /// Size of a single sample in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            DataType::UInt8 | DataType::Int8 => 1,
+            DataType::UInt16 | DataType::Int16 => 2,
+            DataType::UInt32 | DataType::Int32 | DataType::Float32 => 4,
+            DataType::UInt64 | DataType::Int64 | DataType::Float64 => 8,
+        }
+    }
+}
+
+/// Color interpretation of a band, as returned by [`CogReader::color_interpretation`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorInterp {
+    /// Grayscale intensity
+    Gray,
+    /// Red channel of an RGB image
+    Red,
+    /// Green channel of an RGB image
+    Green,
+    /// Blue channel of an RGB image
+    Blue,
+    /// Alpha (transparency) channel
+    Alpha,
+    /// Indices into the [`colormap`](CogReader::colormap) of a paletted image
+    Palette,
+    /// No known color interpretation
+    Undefined,
+}
+
+/// Byte order (endianness) of a TIFF file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// Little-endian, with the byte order mark "II" (Intel)
+    LittleEndian,
+    /// Big-endian, with the byte order mark "MM" (Motorola)
+    BigEndian,
+}
+
+/// Position and dimensions of a tile (or strip) within a GeoTIFF image, in pixels
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TilePos {
+    /// Column offset of the tile's upper-left pixel from the image's upper-left pixel
+    pub col_off: u32,
+    /// Row offset of the tile's upper-left pixel from the image's upper-left pixel
+    pub row_off: u32,
+    /// Width of the decoded tile
+    pub width: u32,
+    /// Height of the decoded tile
+    pub height: u32,
+}
+
+/// Dataset and band-level metadata items stored in the GDAL_METADATA TIFF tag (42112)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GdalMetadata {
+    /// Dataset-level metadata items, keyed by name
+    pub dataset: HashMap<String, String>,
+    /// Band-level metadata items for each band, keyed by name
+    pub bands: Vec<HashMap<String, String>>,
+}
+
+/// Georeferenced 3D array of shape (band, height, width) with labeled coordinates, as
+/// returned by [`CogReader::read_geo_array`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeoArray3<T> {
+    /// Pixel data of shape (band, height, width)
+    pub data: Array3<T>,
+    /// x-coordinates of the pixel centers along the width axis
+    pub x: Array1<f64>,
+    /// y-coordinates of the pixel centers along the height axis
+    pub y: Array1<f64>,
+    /// Affine transformation from pixel (column, row) to world (x, y) coordinates
+    pub transform: AffineTransform<f64>,
+    /// EPSG code of the coordinate reference system, if known
+    pub epsg: Option<u32>,
+}
+
+/// Provenance of a TIFF file from its baseline TIFF tags, as returned by
+/// [`CogReader::provenance`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Provenance {
+    /// Date and time of image creation from the DateTime TIFF tag (306), in the format
+    /// "YYYY:MM:DD HH:MM:SS"
+    pub date_time: Option<String>,
+    /// Name and version of the software that created the image, from the Software TIFF tag
+    /// (305), e.g. "GDAL 3.8.4"
+    pub software: Option<String>,
+}
+
+/// Problem with the layout of a TIFF file that makes it not (fully) cloud-optimized, as
+/// reported by [`validate_cog`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CogWarning {
+    /// Image File Directory (IFD) is stored after some of the pixel data, so the file
+    /// cannot be read by fetching its headers first
+    IfdAfterData {
+        /// Index of the IFD
+        ifd_index: usize,
+    },
+    /// Image larger than 512x512 pixels is stored in strips instead of internal tiles
+    NotTiled {
+        /// Index of the IFD
+        ifd_index: usize,
+    },
+    /// Image larger than 512x512 pixels has no reduced-resolution overviews
+    NoOverviews,
+    /// Overview is not decimated by a power of two relative to the full-resolution image,
+    /// or is not smaller than the previous overview
+    OverviewNotPowerOfTwo {
+        /// Index of the IFD
+        ifd_index: usize,
+        /// Decimation factor of the overview relative to the full-resolution image
+        decimation: u32,
+    },
+    /// Offsets of the tiles (or strips) are not monotonically increasing
+    ChunkOffsetsNotIncreasing {
+        /// Index of the IFD
+        ifd_index: usize,
+    },
+}
+
+impl std::fmt::Display for CogWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CogWarning::IfdAfterData { ifd_index } => {
+                write!(f, "IFD {ifd_index} is stored after pixel data")
+            }
+            CogWarning::NotTiled { ifd_index } => {
+                write!(f, "IFD {ifd_index} is larger than 512x512 but not tiled")
+            }
+            CogWarning::NoOverviews => {
+                write!(f, "Image is larger than 512x512 but has no overviews")
+            }
+            CogWarning::OverviewNotPowerOfTwo {
+                ifd_index,
+                decimation,
+            } => write!(
+                f,
+                "Overview in IFD {ifd_index} has decimation factor {decimation}, which is not \
+                 an increasing power of two"
+            ),
+            CogWarning::ChunkOffsetsNotIncreasing { ifd_index } => {
+                write!(f, "Tile offsets of IFD {ifd_index} are not increasing")
+            }
+        }
+    }
+}
+
+/// Method used to resample image pixels to a different output size
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resampling {
+    /// Value of the source pixel nearest to the center of each output pixel
+    Nearest,
+    /// Linear interpolation between the four source pixels nearest to the center of each
+    /// output pixel
+    Bilinear,
+    /// Mean of all source pixels that overlap each output pixel
+    Average,
+}
+
+/// Parse the XML content of a GDAL_METADATA TIFF tag, which looks like:
+///
+/// ```xml
+/// <GDALMetadata>
+///   <Item name="AREA_OR_POINT">Area</Item>
+///   <Item name="DESCRIPTION" sample="0" role="description">red</Item>
+/// </GDALMetadata>
+/// ```
+fn parse_gdal_metadata(xml: &str, num_bands: usize) -> GdalMetadata {
+    let mut gdal_metadata = GdalMetadata {
+        dataset: HashMap::new(),
+        bands: vec![HashMap::new(); num_bands],
+    };
+
+    for item in xml.split("<Item").skip(1) {
+        let Some((attributes, rest)) = item.split_once('>') else {
+            continue;
+        };
+        // Handle both <Item name="..."/> and <Item name="...">value</Item>
+        let (attributes, value): (&str, &str) = match attributes.strip_suffix('/') {
+            Some(attributes) => (attributes, ""),
+            None => (
+                attributes,
+                rest.split_once("</Item>").map_or(rest, |(value, _)| value),
+            ),
+        };
+        let Some(name) = xml_attribute(attributes, "name") else {
+            continue;
+        };
+        if xml_attribute(attributes, "domain").is_some_and(|domain| !domain.is_empty()) {
+            continue;
+        }
+
+        let (name, value): (String, String) = (xml_unescape(name), xml_unescape(value.trim()));
+        match xml_attribute(attributes, "sample").and_then(|sample| sample.parse::<usize>().ok()) {
+            Some(band) => {
+                if band >= gdal_metadata.bands.len() {
+                    gdal_metadata.bands.resize(band + 1, HashMap::new());
+                }
+                gdal_metadata.bands[band].insert(name, value);
+            }
+            None => {
+                gdal_metadata.dataset.insert(name, value);
+            }
+        }
+    }
+
+    gdal_metadata
+}
+
+/// Get the value of a named attribute from the attributes of an XML element
+fn xml_attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let (_, rest) = attributes.split_once(&format!(" {name}=\""))?;
+    let (value, _) = rest.split_once('"')?;
+
+    Some(value)
+}
+
+/// Replace the predefined XML entities with the characters that they represent
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Resample an array of shape (band, height, width) along its height and width axes to
+/// shape (band, out_height, out_width)
+fn resample(
+    array: &Array3<f64>,
+    out_width: usize,
+    out_height: usize,
+    method: Resampling,
+) -> Array3<f64> {
+    let (num_bands, height, width) = array.dim();
+    let x_scale: f64 = width as f64 / out_width as f64;
+    let y_scale: f64 = height as f64 / out_height as f64;
+
+    Array3::from_shape_fn((num_bands, out_height, out_width), |(band, row, col)| {
+        match method {
+            Resampling::Nearest => {
+                let src_row: usize = (((row as f64 + 0.5) * y_scale) as usize).min(height - 1);
+                let src_col: usize = (((col as f64 + 0.5) * x_scale) as usize).min(width - 1);
+                array[[band, src_row, src_col]]
+            }
+            Resampling::Bilinear => {
+                // Position of output pixel center in source pixel index space
+                let y: f64 = ((row as f64 + 0.5) * y_scale - 0.5).clamp(0.0, (height - 1) as f64);
+                let x: f64 = ((col as f64 + 0.5) * x_scale - 0.5).clamp(0.0, (width - 1) as f64);
+                let (row0, col0): (usize, usize) = (y.floor() as usize, x.floor() as usize);
+                let row1: usize = (row0 + 1).min(height - 1);
+                let col1: usize = (col0 + 1).min(width - 1);
+                let (dy, dx): (f64, f64) = (y - row0 as f64, x - col0 as f64);
+
+                let top: f64 =
+                    array[[band, row0, col0]] * (1.0 - dx) + array[[band, row0, col1]] * dx;
+                let bottom: f64 =
+                    array[[band, row1, col0]] * (1.0 - dx) + array[[band, row1, col1]] * dx;
+                top * (1.0 - dy) + bottom * dy
+            }
+            Resampling::Average => {
+                // Range of source pixels overlapping the output pixel, at least one wide
+                let row_start: usize = (row as f64 * y_scale) as usize;
+                let row_end: usize =
+                    (((row + 1) as f64 * y_scale).ceil() as usize).clamp(row_start + 1, height);
+                let col_start: usize = (col as f64 * x_scale) as usize;
+                let col_end: usize =
+                    (((col + 1) as f64 * x_scale).ceil() as usize).clamp(col_start + 1, width);
+                array
+                    .slice(s![band, row_start..row_end, col_start..col_end])
+                    .mean()
+                    .unwrap()
+            }
+        }
+    })
+}
+
+/// Parse the GDAL structural metadata (ghost area) starting at `offset` (right after the
+/// TIFF header), returning `None` if the file does not start with one
+fn read_ghost_header<R: Read + Seek>(
+    stream: &mut R,
+    offset: u64,
+) -> TiffResult<Option<GhostHeader>> {
+    // Size line is of the form "GDAL_STRUCTURAL_METADATA_SIZE=000140 bytes\n"
+    const PREFIX: &[u8] = b"GDAL_STRUCTURAL_METADATA_SIZE=";
+    let mut size_line = [0u8; 43];
+    stream.seek(SeekFrom::Start(offset))?;
+    match stream.read_exact(&mut size_line) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err.into()),
+    }
+    if !size_line.starts_with(PREFIX) {
+        return Ok(None);
+    }
+    let Some(size) = std::str::from_utf8(&size_line[PREFIX.len()..PREFIX.len() + 6])
+        .ok()
+        .and_then(|size| size.parse::<usize>().ok())
+    else {
+        return Ok(None);
+    };
+
+    // Parse "KEY=VALUE" items, one per line
+    let mut metadata: Vec<u8> = vec![0; size];
+    stream.read_exact(&mut metadata)?;
+    let items: Vec<(String, String)> = String::from_utf8_lossy(&metadata)
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let has_item = |key: &str, value: &str| items.iter().any(|(k, v)| k == key && v == value);
+
+    Ok(Some(GhostHeader {
+        ifds_before_data: has_item("LAYOUT", "IFDS_BEFORE_DATA"),
+        block_order_row_major: has_item("BLOCK_ORDER", "ROW_MAJOR"),
+        block_leader_size_as_uint4: has_item("BLOCK_LEADER", "SIZE_AS_UINT4"),
+        block_trailer_last_4_bytes_repeated: has_item("BLOCK_TRAILER", "LAST_4_BYTES_REPEATED"),
+        mask_interleaved_with_imagery: has_item("MASK_INTERLEAVED_WITH_IMAGERY", "YES"),
+        known_incompatible_edition: has_item("KNOWN_INCOMPATIBLE_EDITION", "YES"),
+        items,
+    }))
+}
+
+/// Byte offsets of every Image File Directory (IFD) in a TIFF file, found by following
+/// the chain of next IFD offsets from the TIFF header
+fn ifd_offsets<R: Read + Seek>(stream: &mut R) -> TiffResult<Vec<u64>> {
+    let mut header = [0u8; 16];
+    stream.seek(SeekFrom::Start(0))?;
+    stream.read_exact(&mut header[..8])?;
+    let big_endian: bool = &header[0..2] == b"MM";
+    let read_uint = |bytes: &[u8]| -> u64 {
+        let mut buffer = [0u8; 8];
+        match big_endian {
+            true => {
+                buffer[8 - bytes.len()..].copy_from_slice(bytes);
+                u64::from_be_bytes(buffer)
+            }
+            false => {
+                buffer[..bytes.len()].copy_from_slice(bytes);
+                u64::from_le_bytes(buffer)
+            }
+        }
+    };
+
+    // Sizes of the entry count, an IFD entry and an offset, for classic TIFF or BigTIFF
+    let is_bigtiff: bool = read_uint(&header[2..4]) == 43;
+    let (count_size, entry_size, offset_size): (usize, u64, usize) = match is_bigtiff {
+        true => (8, 20, 8),
+        false => (2, 12, 4),
+    };
+    let mut ifd_offset: u64 = match is_bigtiff {
+        true => {
+            stream.read_exact(&mut header[8..16])?;
+            read_uint(&header[8..16])
+        }
+        false => read_uint(&header[4..8]),
+    };
+
+    let mut ifd_offsets: Vec<u64> = vec![];
+    while ifd_offset != 0 && !ifd_offsets.contains(&ifd_offset) {
+        ifd_offsets.push(ifd_offset);
+
+        let mut buffer = [0u8; 8];
+        stream.seek(SeekFrom::Start(ifd_offset))?;
+        stream.read_exact(&mut buffer[..count_size])?;
+        let num_entries: u64 = read_uint(&buffer[..count_size]);
+        stream.seek(SeekFrom::Current((num_entries * entry_size) as i64))?;
+        stream.read_exact(&mut buffer[..offset_size])?;
+        ifd_offset = read_uint(&buffer[..offset_size]);
+    }
+    stream.seek(SeekFrom::Start(0))?;
+
+    Ok(ifd_offsets)
+}
+
+/// Flip and/or transpose an array of shape (band, height, width) as stored in the TIFF
+/// file with the given Orientation tag value, so that it has a top-left origin
+fn apply_orientation<T: Clone>(mut array: Array3<T>, orientation: u16) -> Array3<T> {
+    // Orientations 5 to 8 store the visual columns as rows
+    if (5..=8).contains(&orientation) {
+        array.swap_axes(1, 2);
+    }
+    let (flip_rows, flip_cols): (bool, bool) = orientation_flips(orientation);
+    if flip_rows {
+        array.invert_axis(Axis(1));
+    }
+    if flip_cols {
+        array.invert_axis(Axis(2));
+    }
+
+    if array.is_standard_layout() {
+        return array;
+    }
+    array.as_standard_layout().into_owned()
+}
+
+/// Whether the rows and/or columns are flipped for the given Orientation tag value, after
+/// transposing the stored image for orientations 5 to 8
+fn orientation_flips(orientation: u16) -> (bool, bool) {
+    match orientation {
+        2 | 6 => (false, true),
+        3 | 7 => (true, true),
+        4 | 8 => (true, false),
+        _ => (false, false),
+    }
+}
+
+/// Position of a tile (or window) of the stored image within the visual image of the
+/// given width and height, for the given Orientation tag value
+fn oriented_tile_pos(tile_pos: TilePos, orientation: u16, (width, height): (u32, u32)) -> TilePos {
+    let mut tile_pos: TilePos = match (5..=8).contains(&orientation) {
+        true => TilePos {
+            col_off: tile_pos.row_off,
+            row_off: tile_pos.col_off,
+            width: tile_pos.height,
+            height: tile_pos.width,
+        },
+        false => tile_pos,
+    };
+    let (flip_rows, flip_cols): (bool, bool) = orientation_flips(orientation);
+    if flip_rows {
+        tile_pos.row_off = height - tile_pos.row_off - tile_pos.height;
+    }
+    if flip_cols {
+        tile_pos.col_off = width - tile_pos.col_off - tile_pos.width;
+    }
+
+    tile_pos
+}
+
+/// Position of a window of the visual image of the given width and height within the
+/// stored image, i.e. the inverse of [`oriented_tile_pos`]
+fn stored_tile_pos(window: TilePos, orientation: u16, (width, height): (u32, u32)) -> TilePos {
+    let mut window: TilePos = window;
+    let (flip_rows, flip_cols): (bool, bool) = orientation_flips(orientation);
+    if flip_rows {
+        window.row_off = height - window.row_off - window.height;
+    }
+    if flip_cols {
+        window.col_off = width - window.col_off - window.width;
+    }
+    match (5..=8).contains(&orientation) {
+        true => TilePos {
+            col_off: window.row_off,
+            row_off: window.col_off,
+            width: window.height,
+            height: window.width,
+        },
+        false => window,
+    }
+}
+
+/// Replace an unexpected end of file error while decoding pixel data with a clearer error
+/// pointing at a truncated file (e.g. an incomplete download), passing other errors through
+fn truncation_error(err: TiffError) -> TiffError {
+    match err {
+        TiffError::IoError(io_err) if io_err.kind() == io::ErrorKind::UnexpectedEof => {
+            TiffError::FormatError(TiffFormatError::Format(format!(
+                "Image data ended unexpectedly ({io_err}), the file may be truncated or corrupt"
+            )))
+        }
+        _ => err,
+    }
+}
+
+/// Name of a TIFF compression method, for use in error messages
+fn compression_name(code: u16) -> &'static str {
+    match code {
+        2 => "CCITT RLE",
+        3 => "CCITT Group 3 fax",
+        4 => "CCITT Group 4 fax",
+        6 => "Old-style JPEG",
+        7 => "JPEG",
+        34712 => "JPEG 2000",
+        34887 => "LERC",
+        34925 => "LZMA",
+        50000 => "ZSTD",
+        50001 => "WebP",
+        50002 => "JPEG XL",
+        _ => "Unknown",
+    }
+}
+
+/// Whether the NewSubfileType bit flags of an Image File Directory (IFD) denote a
+/// full-resolution image, i.e. not an overview or a transparency mask
+fn is_full_image(subfile_type: u32) -> bool {
+    subfile_type & (SUBFILE_REDUCED_IMAGE | SUBFILE_MASK) == 0
+}
+
+/// Convert decoded TIFF pixel data of any dtype to a [`Vec`] of dtype `T`
+fn decoding_result_to_vec<T: FromPrimitive>(decode_result: DecodingResult) -> Vec<T> {
+    match decode_result {
+        DecodingResult::U8(img_data) => img_data.iter().map(|v| T::from_u8(*v).unwrap()).collect(),
+        DecodingResult::U16(img_data) => {
+            img_data.iter().map(|v| T::from_u16(*v).unwrap()).collect()
+        }
+        DecodingResult::U32(img_data) => {
+            img_data.iter().map(|v| T::from_u32(*v).unwrap()).collect()
+        }
+        DecodingResult::U64(img_data) => {
+            img_data.iter().map(|v| T::from_u64(*v).unwrap()).collect()
+        }
+        DecodingResult::I8(img_data) => img_data.iter().map(|v| T::from_i8(*v).unwrap()).collect(),
+        DecodingResult::I16(img_data) => {
+            img_data.iter().map(|v| T::from_i16(*v).unwrap()).collect()
+        }
+        DecodingResult::I32(img_data) => {
+            img_data.iter().map(|v| T::from_i32(*v).unwrap()).collect()
+        }
+        DecodingResult::I64(img_data) => {
+            img_data.iter().map(|v| T::from_i64(*v).unwrap()).collect()
+        }
+        DecodingResult::F32(img_data) => {
+            img_data.iter().map(|v| T::from_f32(*v).unwrap()).collect()
+        }
+        DecodingResult::F64(img_data) => {
+            img_data.iter().map(|v| T::from_f64(*v).unwrap()).collect()
+        }
+    }
+}
+
+/// Synchronously read a GeoTIFF file into an [`ndarray::Array`]
+pub fn read_geotiff<T: FromPrimitive + Clone + 'static, R: Read + Seek>(
+    stream: R,
+) -> TiffResult<Array3<T>> {
+    // Open TIFF stream with decoder
+    let mut reader = CogReader::new(stream)?;
+
+    // Decode TIFF into ndarray
+    let array_data: Array3<T> = reader.ndarray()?;
+
+    Ok(array_data)
+}
+
+/// Synchronously read a GeoTIFF file into a flat Apache Arrow array (e.g. for columnar
+/// query engines such as Polars or DataFusion), along with the shape (band, height, width)
+/// of the image. Requires the `arrow` feature.
+///
+/// The array has the dtype of the samples in the file (e.g. [`UInt16Array`] for uint16
+/// samples), also for images with sparse tiles (or strips), and takes ownership of the
+/// decoded pixel data without copying it. As with [`read_geotiff`], the image is flipped
+/// and/or transposed according to its [orientation](CogReader::orientation).
+#[cfg(feature = "arrow")]
+pub fn read_arrow<R: Read + Seek>(stream: R) -> TiffResult<(ArrayRef, (usize, usize, usize))> {
+    let mut reader = CogReader::new(stream)?;
+    let arrow_data: (ArrayRef, (usize, usize, usize)) = match reader.data_type()? {
+        DataType::UInt8 => arrow_parts::<u8, UInt8Array>(reader.decode_ndarray()?),
+        DataType::UInt16 => arrow_parts::<u16, UInt16Array>(reader.decode_ndarray()?),
+        DataType::UInt32 => arrow_parts::<u32, UInt32Array>(reader.decode_ndarray()?),
+        DataType::UInt64 => arrow_parts::<u64, UInt64Array>(reader.decode_ndarray()?),
+        DataType::Int8 => arrow_parts::<i8, Int8Array>(reader.decode_ndarray()?),
+        DataType::Int16 => arrow_parts::<i16, Int16Array>(reader.decode_ndarray()?),
+        DataType::Int32 => arrow_parts::<i32, Int32Array>(reader.decode_ndarray()?),
+        DataType::Int64 => arrow_parts::<i64, Int64Array>(reader.decode_ndarray()?),
+        DataType::Float32 => arrow_parts::<f32, Float32Array>(reader.decode_ndarray()?),
+        DataType::Float64 => arrow_parts::<f64, Float64Array>(reader.decode_ndarray()?),
+    };
+
+    Ok(arrow_data)
+}
+
+/// Move the samples of a decoded array (in standard layout) into a flat Arrow array of type
+/// `A`, along with the shape of the decoded array
+#[cfg(feature = "arrow")]
+fn arrow_parts<T, A>(array_data: Array3<T>) -> (ArrayRef, (usize, usize, usize))
+where
+    A: arrow_array::Array + From<Vec<T>> + 'static,
+{
+    let shape: (usize, usize, usize) = array_data.dim();
+    let array: ArrayRef = Arc::new(A::from(array_data.into_raw_vec()));
+
+    (array, shape)
+}
+
+/// Synchronously read a GeoTIFF file into an [`ndarray::Array`] in Fortran (column-major)
+/// memory order, e.g. for linear algebra routines that expect column-major arrays.
+///
+/// The array has the same shape (band, height, width) and values as the one returned by
+/// [`read_geotiff`], which is in C (row-major) order, but with the width axis varying
+/// slowest in memory. The decoded image is copied once to change the memory order.
+pub fn read_geotiff_fortran<T: FromPrimitive + Clone + 'static, R: Read + Seek>(
+    stream: R,
+) -> TiffResult<Array3<T>> {
+    let array_data: Array3<T> = read_geotiff(stream)?;
+
+    to_fortran_order(array_data)
+}
+
+/// Serialize a decoded array into a flat buffer of little-endian bytes in (band, height,
+/// width) order, along with its shape
+fn raw_parts<T: ToBytes + Clone>(array_data: Array3<T>) -> (Vec<u8>, (usize, usize, usize)) {
+    let shape: (usize, usize, usize) = array_data.dim();
+    let bytes: Vec<u8> = match array_data.as_slice() {
+        Some(samples) => to_le_bytes(samples),
+        None => to_le_bytes(&array_data.iter().cloned().collect::<Vec<T>>()),
+    };
+
+    (bytes, shape)
+}
+
+/// Serialize samples into a flat buffer of little-endian bytes
+fn to_le_bytes<T: ToBytes>(samples: &[T]) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(std::mem::size_of_val(samples));
+    for sample in samples {
+        bytes.extend_from_slice(sample.to_le_bytes().as_ref());
+    }
+    bytes
+}
+
+/// Convert scaled values to the requested dtype, returning an error if any of them (e.g.
+/// NaN, or a negative value for an unsigned dtype) cannot be represented in it
+fn scaled_to_dtype<T: FromPrimitive + Clone>(array_data: Array3<f64>) -> TiffResult<Array3<T>> {
+    if let Some(value) = array_data.iter().find(|v| T::from_f64(**v).is_none()) {
+        return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+            "Scaled value {value} cannot be represented in the requested dtype, use a float \
+             dtype instead"
+        ))));
+    }
+
+    Ok(array_data.mapv(|value| T::from_f64(value).unwrap()))
+}
+
+/// Copy an array into Fortran (column-major) memory order
+fn to_fortran_order<T: Clone>(array_data: Array3<T>) -> TiffResult<Array3<T>> {
+    // Iterating over the transposed array visits the elements in column-major order
+    let fortran_data: Vec<T> = array_data.t().iter().cloned().collect();
+    let array_data: Array3<T> = Array3::from_shape_vec(array_data.raw_dim().f(), fortran_data)
+        .map_err(|_| TiffFormatError::InconsistentSizesEncountered)?;
+
+    Ok(array_data)
+}
+
+/// Synchronously read a GeoTIFF file into an [`ndarray::Array`] of dtype `Dst`, regardless
+/// of the dtype of the samples in the file, e.g. to get float32 values from a uint16 file.
+///
+/// The samples are decoded in their native dtype, and then converted to physical values
+/// using the per-band [scale and offset](CogReader::scale_offset) (if present) before being
+/// cast to `Dst`. The cast follows Rust's `as` semantics, i.e. float values are truncated
+/// towards zero and saturate at the bounds of integer dtypes, with NaN becoming 0.
+///
+/// Note that values are converted via f64, so uint64/int64 values larger than 2^53 lose
+/// precision, as do values larger than 2^24 when cast to f32.
+pub fn read_geotiff_as<Dst, R: Read + Seek>(stream: R) -> TiffResult<Array3<Dst>>
+where
+    Dst: Copy + 'static,
+    f64: AsPrimitive<Dst>,
+{
+    let mut reader = CogReader::new(stream)?;
+    let scale_offset: Vec<(f64, f64)> = reader.scale_offset()?;
+    let mut array_data: Array3<f64> = reader.ndarray()?;
+
+    // Apply scale and offset of each band
+    for (mut band, (scale, offset)) in array_data.outer_iter_mut().zip(scale_offset) {
+        if scale != 1.0 || offset != 0.0 {
+            band.mapv_inplace(|value| value * scale + offset);
+        }
+    }
+
+    Ok(array_data.mapv(|value| value.as_()))
+}
+
+/// Read many GeoTIFF files (e.g. a directory of tiles) concurrently on a thread pool, each
+/// into an [`ndarray::Array`] of shape (band, height, width). Requires the `parallel`
+/// feature.
+///
+/// The results are returned in the same order as `sources`, with a separate result for
+/// each file, so that one unreadable file does not prevent reading the others. The files
+/// are decoded on at most `max_threads` threads, or on rayon's global thread pool (with one
+/// thread per CPU core by default) if `None`. Returns an error if the thread pool cannot be
+/// created.
+#[cfg(feature = "parallel")]
+pub fn read_geotiff_many<T, R>(
+    sources: Vec<R>,
+    max_threads: Option<usize>,
+) -> TiffResult<Vec<TiffResult<Array3<T>>>>
+where
+    T: FromPrimitive + Clone + Send + 'static,
+    R: Read + Seek + Send,
+{
+    let read_all = || -> Vec<TiffResult<Array3<T>>> {
+        sources.into_par_iter().map(read_geotiff::<T, R>).collect()
+    };
+
+    match max_threads {
+        Some(num_threads) => {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .map_err(io::Error::other)?;
+            Ok(pool.install(read_all))
+        }
+        None => Ok(read_all()),
+    }
+}
+
+/// Synchronously read several GeoTIFF files with the same shape (e.g. a time-series on the
+/// same grid) into a stacked 4D [`ndarray::Array`] of shape (time, band, height, width).
+///
+/// Returns an error identifying the first file whose (band, height, width) shape differs
+/// from that of the first file, or if no files are given.
+pub fn read_geotiff_stack<T: FromPrimitive + Clone + 'static, R: Read + Seek>(
+    streams: Vec<R>,
+) -> TiffResult<Array4<T>> {
+    let arrays: Vec<Array3<T>> = streams
+        .into_iter()
+        .map(read_geotiff::<T, R>)
+        .collect::<TiffResult<_>>()?;
+
+    // Check that all arrays have the same shape as the first one
+    let Some(first_array) = arrays.first() else {
+        return Err(TiffError::FormatError(TiffFormatError::Format(
+            String::from("Cannot stack an empty list of GeoTIFF files"),
+        )));
+    };
+    let shape = first_array.dim();
+    if let Some((index, array)) = arrays
+        .iter()
+        .enumerate()
+        .find(|(_, array)| array.dim() != shape)
+    {
+        return Err(TiffError::FormatError(TiffFormatError::Format(format!(
+            "Shape {:?} of file {index} does not match shape {shape:?} of file 0",
+            array.dim()
+        ))));
+    }
+
+    // Stack arrays along a new leading (time) axis
+    let views: Vec<ArrayView3<T>> = arrays.iter().map(|array| array.view()).collect();
+    let array_data: Array4<T> = ndarray::stack(Axis(0), &views)
+        .map_err(|_| TiffFormatError::InconsistentSizesEncountered)?;
+
+    Ok(array_data)
+}
+
+/// Check whether a TIFF file is laid out as a valid Cloud-optimized GeoTIFF (COG), similar
+/// to `rio cogeo validate`.
+///
+/// The following checks are done, with a [`CogWarning`] returned for each problem found
+/// (so an empty list means the file is a valid COG):
+///
+/// - All Image File Directories (IFDs) are stored before the pixel data
+/// - Images larger than 512x512 pixels are internally tiled
+/// - Images larger than 512x512 pixels have overviews, which are decimated by increasing
+///   powers of two
+/// - Offsets of the tiles (or strips) in each IFD are monotonically increasing
+pub fn validate_cog<R: Read + Seek>(mut stream: R) -> TiffResult<Vec<CogWarning>> {
+    let ifd_offsets: Vec<u64> = ifd_offsets(&mut stream)?;
+    let mut reader = CogReader::new(stream)?;
+    let mut warnings: Vec<CogWarning> = vec![];
+
+    // Check layout of tiles (or strips) in every IFD
+    let mut first_data_offset: u64 = u64::MAX;
+    for ifd_index in 0..ifd_offsets.len() {
+        reader.seek_to_ifd(ifd_index)?;
+        let (width, height): (u32, u32) = reader.dimensions()?;
+        let offsets_tag: Tag = match reader.decoder.get_chunk_type() {
+            ChunkType::Tile => Tag::TileOffsets,
+            ChunkType::Strip => {
+                if width > 512 || height > 512 {
+                    warnings.push(CogWarning::NotTiled { ifd_index });
+                }
+                Tag::StripOffsets
+            }
+        };
+
+        // Offsets of sparse chunks are zero, and are skipped
+        let chunk_offsets: Vec<u64> = reader
+            .decoder
+            .get_tag_u64_vec(offsets_tag)?
+            .into_iter()
+            .filter(|offset| *offset != 0)
+            .collect();
+        if chunk_offsets.windows(2).any(|pair| pair[1] < pair[0]) {
+            warnings.push(CogWarning::ChunkOffsetsNotIncreasing { ifd_index });
+        }
+        if let Some(min_offset) = chunk_offsets.iter().min() {
+            first_data_offset = first_data_offset.min(*min_offset);
+        }
+    }
+
+    // Check that IFDs come before the pixel data
+    for (ifd_index, ifd_offset) in ifd_offsets.iter().enumerate() {
+        if *ifd_offset > first_data_offset {
+            warnings.push(CogWarning::IfdAfterData { ifd_index });
+        }
+    }
+
+    // Check overviews of the full-resolution image
+    reader.seek_to_ifd(0)?;
+    let (width, height): (u32, u32) = reader.dimensions()?;
+    let overview_ifd_indices: Vec<usize> = reader.overview_ifd_indices()?;
+    if overview_ifd_indices.is_empty() && (width > 512 || height > 512) {
+        warnings.push(CogWarning::NoOverviews);
+    }
+    let mut previous_decimation: u32 = 1;
+    for ifd_index in overview_ifd_indices {
+        reader.seek_to_ifd(ifd_index)?;
+        let (overview_width, _overview_height): (u32, u32) = reader.dimensions()?;
+        let decimation: u32 = (width as f64 / overview_width as f64).round() as u32;
+        if !decimation.is_power_of_two() || decimation <= previous_decimation {
+            warnings.push(CogWarning::OverviewNotPowerOfTwo {
+                ifd_index,
+                decimation,
+            });
+        }
+        previous_decimation = decimation;
+    }
+
+    Ok(warnings)
+}
+
+/// Asynchronously read a GeoTIFF file from an [`ObjectStore`] into an [`ndarray::Array`]
+///
+/// The whole file is fetched into an in-memory buffer with a single GET request before
+/// being decoded. This trades memory (the entire file is held at once) for fewer network
+/// round trips compared to issuing a ranged request for every tile. For local files, or
+/// data already in a `Read + Seek` stream, use the synchronous [`read_geotiff`] instead.
+pub async fn read_geotiff_async<T: FromPrimitive + Clone + 'static>(
+    store: &dyn ObjectStore,
+    location: &Path,
+) -> TiffResult<Array3<T>> {
+    // Get TIFF file stream asynchronously
+    let result: GetResult = store.get(location).await.map_err(io::Error::from)?;
+    let bytes: Bytes = result.bytes().await.map_err(io::Error::from)?;
+
+    // Decode TIFF into ndarray
+    let array_data: Array3<T> = read_geotiff(Cursor::new(bytes))?;
+
+    Ok(array_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::fs::File;
+    use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+    use std::ops::Range;
+    use std::rc::Rc;
+
+    use bytes::Bytes;
+    use geo::AffineTransform;
+    use ndarray::{array, s, Array2, Array3, Order};
+    use object_store::parse_url;
+    use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+    use tempfile::tempfile;
+    use tiff::decoder::Limits;
+    use tiff::encoder::{colortype, TiffEncoder};
+    use tiff::tags::{CompressionMethod, PhotometricInterpretation, Predictor, SampleFormat, Tag};
+    use tiff::{ColorType, TiffError, TiffUnsupportedError};
+    use url::Url;
+
+    use crate::io::geotiff::{
+        parse_gdal_metadata, read_geotiff, read_geotiff_as, read_geotiff_async,
+        read_geotiff_fortran, read_geotiff_stack, validate_cog, ByteOrder, CogReader,
+        CogReaderBuilder, CogReaderPool, CogWarning, ColorInterp, DataType, Provenance, Resampling,
+        TileChecksums, TilePos, ZarrChunkRef,
+    };
+
+    /// Write a single-band float32 GeoTIFF with the given ModelPixelScaleTag and
+    /// ModelTiepointTag values to a temporary file
+    fn geotiff_tempfile(width: u32, height: u32, pixel_scale: &[f64], tie_points: &[f64]) -> File {
+        let image_data: Vec<f32> = (0..width * height).map(|v| v as f32).collect();
+
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff
+            .new_image::<colortype::Gray32Float>(width, height)
+            .unwrap();
+        let encoder = image.encoder();
+        encoder
+            .write_tag(Tag::ModelPixelScaleTag, pixel_scale)
+            .unwrap();
+        encoder
+            .write_tag(Tag::ModelTiepointTag, tie_points)
+            .unwrap();
+        image.write_data(&image_data).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        file
+    }
+
+    /// Write a 4x3 float32 GeoTIFF with pixel values 0 to 11, a pixel size of 0.5 and its
+    /// upper-left corner at (10, 20) to a temporary file
+    fn georeferenced_tempfile() -> File {
+        geotiff_tempfile(4, 3, &[0.5, 0.5, 0.0], &[0.0, 0.0, 0.0, 10.0, 20.0, 0.0])
+    }
+
+    /// Write a tiled, uncompressed, single-band uint8 TIFF file by hand to a temporary
+    /// file. The `tiles` are given in row-major spatial order, but are stored in the file
+    /// following `storage_order`. Tiles that are `None` are sparse (have zero byte count).
+    fn tiled_tiff_tempfile(
+        width: u32,
+        height: u32,
+        tile_size: u32,
+        tiles: &[Option<Vec<u8>>],
+        storage_order: &[usize],
+    ) -> File {
+        let num_tiles = tiles.len() as u32;
+        let num_entries: u16 = 10;
+        let ifd_offset: u32 = 8;
+        let tile_offsets_offset: u32 = ifd_offset + 2 + num_entries as u32 * 12 + 4;
+        let tile_byte_counts_offset: u32 = tile_offsets_offset + num_tiles * 4;
+
+        // Lay out tile data after the IFD, following the storage order
+        let mut tile_offsets: Vec<u32> = vec![0; tiles.len()];
+        let mut tile_byte_counts: Vec<u32> = vec![0; tiles.len()];
+        let mut tile_data: Vec<u8> = vec![];
+        let mut data_offset: u32 = tile_byte_counts_offset + num_tiles * 4;
+        for &tile_index in storage_order {
+            if let Some(data) = &tiles[tile_index] {
+                tile_offsets[tile_index] = data_offset;
+                tile_byte_counts[tile_index] = data.len() as u32;
+                tile_data.extend(data);
+                data_offset += data.len() as u32;
+            }
+        }
+
+        // TIFF header
+        let mut buffer: Vec<u8> = vec![];
+        buffer.extend(b"II");
+        buffer.extend(42u16.to_le_bytes());
+        buffer.extend(ifd_offset.to_le_bytes());
+
+        // IFD entries of (tag, field type (3=SHORT, 4=LONG), count, value or offset)
+        let entries: [(u16, u16, u32, u32); 10] = [
+            (256, 4, 1, width),                           // ImageWidth
+            (257, 4, 1, height),                          // ImageLength
+            (258, 3, 1, 8),                               // BitsPerSample
+            (259, 3, 1, 1),                               // Compression
+            (262, 3, 1, 1),                               // PhotometricInterpretation
+            (277, 3, 1, 1),                               // SamplesPerPixel
+            (322, 4, 1, tile_size),                       // TileWidth
+            (323, 4, 1, tile_size),                       // TileLength
+            (324, 4, num_tiles, tile_offsets_offset),     // TileOffsets
+            (325, 4, num_tiles, tile_byte_counts_offset), // TileByteCounts
+        ];
+        buffer.extend(num_entries.to_le_bytes());
+        for (tag, field_type, count, value) in entries {
+            buffer.extend(tag.to_le_bytes());
+            buffer.extend(field_type.to_le_bytes());
+            buffer.extend(count.to_le_bytes());
+            buffer.extend(value.to_le_bytes());
+        }
+        buffer.extend(0u32.to_le_bytes()); // no next IFD
+        tile_offsets
+            .iter()
+            .for_each(|offset| buffer.extend(offset.to_le_bytes()));
+        tile_byte_counts
+            .iter()
+            .for_each(|byte_count| buffer.extend(byte_count.to_le_bytes()));
+        buffer.extend(tile_data);
+
+        let mut file = tempfile().unwrap();
+        file.write_all(&buffer).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        file
+    }
+
+    /// Write a stripped, uncompressed, single-band TIFF file with a single strip by hand to
+    /// a temporary file, with the given sample type and Predictor tag value. The `strip`
+    /// bytes are written as is, so any prediction must already be applied to them.
+    fn stripped_tiff_tempfile(
+        width: u32,
+        height: u32,
+        bits_per_sample: u16,
+        sample_format: u16,
+        predictor: u16,
+        strip: &[u8],
+    ) -> File {
+        let num_entries: u16 = 11;
+        let strip_offset: u32 = 8 + 2 + num_entries as u32 * 12 + 4;
+
+        // TIFF header
+        let mut buffer: Vec<u8> = vec![];
+        buffer.extend(b"II");
+        buffer.extend(42u16.to_le_bytes());
+        buffer.extend(8u32.to_le_bytes());
+
+        // IFD entries of (tag, field type (3=SHORT, 4=LONG), value)
+        let entries: [(u16, u16, u32); 11] = [
+            (256, 4, width),                  // ImageWidth
+            (257, 4, height),                 // ImageLength
+            (258, 3, bits_per_sample as u32), // BitsPerSample
+            (259, 3, 1),                      // Compression
+            (262, 3, 1),                      // PhotometricInterpretation
+            (273, 4, strip_offset),           // StripOffsets
+            (277, 3, 1),                      // SamplesPerPixel
+            (278, 4, height),                 // RowsPerStrip
+            (279, 4, strip.len() as u32),     // StripByteCounts
+            (317, 3, predictor as u32),       // Predictor
+            (339, 3, sample_format as u32),   // SampleFormat
+        ];
+        buffer.extend(num_entries.to_le_bytes());
+        for (tag, field_type, value) in entries {
+            buffer.extend(tag.to_le_bytes());
+            buffer.extend(field_type.to_le_bytes());
+            buffer.extend(1u32.to_le_bytes());
+            match field_type {
+                3 => buffer.extend([(value as u16).to_le_bytes(), [0, 0]].concat()),
+                _ => buffer.extend(value.to_le_bytes()),
+            }
+        }
+        buffer.extend(0u32.to_le_bytes()); // no next IFD
+        buffer.extend(strip);
+
+        let mut file = tempfile().unwrap();
+        file.write_all(&buffer).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        file
+    }
+
+    #[test]
+    fn test_read_geotiff() {
+        // Generate some data
+        let mut image_data = Vec::new();
+        for y in 0..10 {
+            for x in 0..20 {
+                let val = y + x;
+                image_data.push(val as f32);
+            }
+        }
+
+        // Write a BigTIFF file
+        let mut file = tempfile().unwrap();
+        let mut bigtiff = TiffEncoder::new_big(&mut file).unwrap();
+        bigtiff
+            .write_image::<colortype::Gray32Float>(20, 10, &image_data) // width, height, data
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        // Read a BigTIFF file
+        let arr = read_geotiff(file).unwrap();
+        assert_eq!(arr.ndim(), 3);
+        assert_eq!(arr.dim(), (1, 10, 20)); // (channels, height, width)
+        let first_band = arr.slice(s![0, .., ..]);
+        assert_eq!(first_band.nrows(), 10); // y-axis
+        assert_eq!(first_band.ncols(), 20); // x-axis
+        assert_eq!(arr.mean(), Some(14.0));
+    }
+
+    #[tokio::test]
+    async fn test_read_geotiff_multi_band() {
+        let cog_url: &str =
+            "https://github.com/locationtech/geotrellis/raw/v3.7.1/raster/data/one-month-tiles-multiband/result.tif";
+        let tif_url = Url::parse(cog_url).unwrap();
+        let (store, location) = parse_url(&tif_url).unwrap();
+
+        let result = store.get(&location).await.unwrap();
+        let bytes = result.bytes().await.unwrap();
+        let stream = Cursor::new(bytes);
+
+        let mut reader = CogReader::new(stream).unwrap();
+        let array = reader.ndarray().unwrap();
+
+        assert_eq!(array.dim(), (2, 512, 512));
+        assert_eq!(array.mean(), Some(225.17439122416545));
+    }
+
+    #[test]
+    fn test_cogreader_ndarray_pixel_interleaved() {
+        // Write a 2x2 RGB TIFF file, whose samples are interleaved pixel by pixel
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::RGB8>(2, 2, &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(
+            reader.ndarray::<u8>().unwrap(),
+            array![[[1, 4], [7, 10]], [[2, 5], [8, 11]], [[3, 6], [9, 12]]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_geotiff_uint16_dtype() {
+        let cog_url: &str =
+            "https://github.com/OSGeo/gdal/raw/v3.9.2/autotest/gcore/data/uint16.tif";
+        let tif_url = Url::parse(cog_url).unwrap();
+        let (store, location) = parse_url(&tif_url).unwrap();
+
+        let result = store.get(&location).await.unwrap();
+        let bytes = result.bytes().await.unwrap();
+        let stream = Cursor::new(bytes);
+
+        let mut reader = CogReader::new(stream).unwrap();
+        let array = reader.ndarray::<u16>().unwrap();
+
+        assert_eq!(array.dim(), (1, 20, 20));
+        assert_eq!(array.mean(), Some(126));
+    }
+
+    #[tokio::test]
+    async fn test_read_geotiff_jpeg_ycbcr() {
+        let cog_url: &str =
+            "https://github.com/developmentseed/async-tiff/raw/30c14c01676d01e3c77f96e751998d4f1d8d1aef/fixtures/image-tiff/tiled-jpeg-ycbcr.tif";
+        let tif_url = Url::parse(cog_url).unwrap();
+        let (store, location) = parse_url(&tif_url).unwrap();
+
+        let result = store.get(&location).await.unwrap();
+        let bytes = result.bytes().await.unwrap();
+        let stream = Cursor::new(bytes);
+
+        let mut reader = CogReader::new(stream).unwrap();
+        assert_eq!(reader.compression().unwrap(), CompressionMethod::ModernJPEG);
+        assert_eq!(reader.color_type().unwrap(), ColorType::YCbCr(8));
+        let array = reader.ndarray::<u8>().unwrap();
+
+        // YCbCr samples are decoded to RGB
+        assert_eq!(array.dim(), (3, 499, 374));
+        assert_eq!(array.slice(s![.., 0, 0]), array![76, 52, 28]);
+        assert_eq!(array.slice(s![.., 100, 50]), array![77, 64, 55]);
+        assert_eq!(array.slice(s![.., 250, 200]), array![253, 254, 255]);
+        assert_eq!(array.slice(s![.., 498, 373]), array![33, 28, 22]);
+    }
+
+    #[test]
+    fn test_cogreader_uncompressed_ycbcr() {
+        // Write an uncompressed TIFF file with YCbCr (PhotometricInterpretation=6) samples
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::RGB8>(1, 1).unwrap();
+        image
+            .encoder()
+            .write_tag(
+                Tag::PhotometricInterpretation,
+                PhotometricInterpretation::YCbCr.to_u16(),
+            )
+            .unwrap();
+        image.write_data(&[0, 128, 128]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        // Only the JPEG decoder converts YCbCr samples to RGB
+        let mut reader = CogReader::new(file).unwrap();
+        let err = reader.ndarray::<u8>().unwrap_err();
+        assert!(matches!(
+            err,
+            TiffError::UnsupportedError(TiffUnsupportedError::UnsupportedColorType(
+                ColorType::YCbCr(8)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cogreader_ndarray() {
+        let cog_url: &str = "https://github.com/rasterio/rasterio/raw/1.3.9/tests/data/float32.tif";
+        let tif_url = Url::parse(cog_url).unwrap();
+        let (store, location) = parse_url(&tif_url).unwrap();
+
+        let result = store.get(&location).await.unwrap();
+        let bytes = result.bytes().await.unwrap();
+        let stream = Cursor::new(bytes);
+
+        let mut reader = CogReader::new(stream).unwrap();
+        let array = reader.ndarray::<f32>().unwrap();
+
+        assert_eq!(array.shape(), [1, 2, 3]);
+        assert_eq!(array, array![[[1.41, 1.23, 0.78], [0.32, -0.23, -1.88]]])
+    }
+
+    #[tokio::test]
+    async fn test_cogreader_transform() {
+        let cog_url: &str =
+            "https://github.com/cogeotiff/rio-tiler/raw/6.4.0/tests/fixtures/cog_nodata_nan.tif";
+        let tif_url = Url::parse(cog_url).unwrap();
+        let (store, location) = parse_url(&tif_url).unwrap();
+
+        let result = store.get(&location).await.unwrap();
+        let bytes = result.bytes().await.unwrap();
+        let stream = Cursor::new(bytes);
+
+        let mut reader = CogReader::new(stream).unwrap();
+        let transform = reader.transform().unwrap();
+
+        assert_eq!(
+            transform,
+            AffineTransform::new(200.0, 0.0, 499980.0, 0.0, -200.0, 5300040.0)
+        );
+    }
+
+    #[test]
+    fn test_cogreader_builder() {
+        // Write a uint8 TIFF file with a scale and offset in the GDAL metadata
+        let xml: &str = r#"<GDALMetadata>
+  <Item name="SCALE" sample="0" role="scale">2</Item>
+  <Item name="OFFSET" sample="0" role="offset">-1</Item>
+</GDALMetadata>"#;
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(3, 2).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::from_u16_exhaustive(42112), xml)
+            .unwrap();
+        image.write_data(&[0, 1, 2, 3, 4, 5]).unwrap();
+        let mut buffer: Vec<u8> = vec![];
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file.read_to_end(&mut buffer).unwrap();
+
+        // Default options are the same as for CogReader::new
+        let mut reader = CogReaderBuilder::new()
+            .build(Cursor::new(buffer.clone()))
+            .unwrap();
+        assert_eq!(
+            reader.ndarray::<f32>().unwrap(),
+            array![[[0., 1., 2.], [3., 4., 5.]]]
+        );
+
+        let mut reader = CogReaderBuilder::new()
+            .bands(vec![0, 0])
+            .apply_scale_offset(true)
+            .order(Order::ColumnMajor)
+            .build(Cursor::new(buffer.clone()))
+            .unwrap();
+        let array = reader.ndarray::<f32>().unwrap();
+        assert_eq!(array.dim(), (2, 2, 3));
+        assert_eq!(
+            array.slice(s![1, .., ..]),
+            array![[-1., 1., 3.], [5., 7., 9.]]
+        );
+        assert!(array.t().is_standard_layout());
+
+        // Scaled values must be representable in integer dtypes
+        let mut reader = CogReaderBuilder::new()
+            .apply_scale_offset(true)
+            .build(Cursor::new(buffer.clone()))
+            .unwrap();
+        assert_eq!(
+            reader.ndarray::<i8>().unwrap(),
+            array![[[-1, 1, 3], [5, 7, 9]]]
+        );
+        let err = reader.ndarray::<u8>().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Scaled value -1 cannot be represented in the requested dtype"));
+
+        let mut reader = CogReaderBuilder::new()
+            .bands(vec![1])
+            .build(Cursor::new(buffer.clone()))
+            .unwrap();
+        let err = reader.ndarray::<f32>().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Band index 1 is out of range for image with 1 bands"));
+
+        // Decoding fails when exceeding the decoder limits
+        let mut limits = Limits::default();
+        limits.decoding_buffer_size = 1;
+        let mut reader = CogReaderBuilder::new()
+            .limits(limits)
+            .build(Cursor::new(buffer))
+            .unwrap();
+        assert!(reader.ndarray::<f32>().is_err());
+    }
+
+    #[test]
+    fn test_cogreader_color_interpretation() {
+        // Write an RGBA TIFF file with unassociated alpha
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::RGBA8>(1, 1).unwrap();
+        image.encoder().write_tag(Tag::ExtraSamples, 2u16).unwrap();
+        image.write_data(&[0, 0, 0, 255]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(
+            reader.color_interpretation().unwrap(),
+            vec![
+                ColorInterp::Red,
+                ColorInterp::Green,
+                ColorInterp::Blue,
+                ColorInterp::Alpha
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cogreader_read_rgba() {
+        // Write a uint8 paletted (PhotometricInterpretation=3) TIFF file with a 256-color
+        // colormap and a nodata value of 0, where color 1 is red, color 2 is green and all
+        // other colors are black
+        let mut colormap: Vec<u16> = vec![0; 3 * 256];
+        colormap[1] = 65535;
+        colormap[256 + 2] = 65535;
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(3, 1).unwrap();
+        image
+            .encoder()
+            .write_tag(
+                Tag::PhotometricInterpretation,
+                PhotometricInterpretation::RGBPalette.to_u16(),
+            )
+            .unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::ColorMap, &colormap[..])
+            .unwrap();
+        image.encoder().write_tag(Tag::GdalNodata, "0").unwrap();
+        image.write_data(&[0, 1, 2]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.color_type().unwrap(), ColorType::Palette(8));
+        let colormap = reader.colormap().unwrap().unwrap();
+        assert_eq!(colormap.len(), 256);
+        assert_eq!(colormap[1], [65535, 0, 0]);
+        assert_eq!(
+            reader.read_rgba().unwrap(),
+            array![[[0, 255, 0]], [[0, 0, 255]], [[0, 0, 0]], [[0, 255, 255]]]
+        );
+    }
+
+    #[test]
+    fn test_cogreader_is_lossy_compression() {
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray8>(1, 1, &[5]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReaderBuilder::new()
+            .require_lossless(true)
+            .build(file)
+            .unwrap();
+        assert!(!reader.is_lossy_compression().unwrap());
+        assert_eq!(reader.ndarray::<u8>().unwrap(), array![[[5]]]);
+
+        // Write a TIFF file that claims to be JPEG compressed
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(1, 1).unwrap();
+        image.encoder().write_tag(Tag::Compression, 7u16).unwrap();
+        image.write_data(&[0]).unwrap();
+        let mut buffer: Vec<u8> = vec![];
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file.read_to_end(&mut buffer).unwrap();
+
+        let mut reader = CogReader::new(Cursor::new(buffer.clone())).unwrap();
+        assert!(reader.is_lossy_compression().unwrap());
+        let mut reader = CogReaderBuilder::new()
+            .require_lossless(true)
+            .build(Cursor::new(buffer))
+            .unwrap();
+        let err = reader.ndarray::<u8>().unwrap_err();
+        assert!(err.to_string().contains(
+            "Image is compressed with lossy JPEG compression (method 7), but exact values \
+             were requested"
+        ));
+    }
+
+    #[test]
+    fn test_cogreader_provenance() {
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(1, 1).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::DateTime, "2024:03:01 12:34:56")
+            .unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::Software, "GDAL 3.8.4")
+            .unwrap();
+        image.write_data(&[0]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(
+            reader.provenance().unwrap(),
+            Provenance {
+                date_time: Some(String::from("2024:03:01 12:34:56")),
+                software: Some(String::from("GDAL 3.8.4")),
+            }
+        );
+    }
+
+    #[test]
+    fn test_cogreader_decoder_mut() {
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(1, 1).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::Unknown(65000), "custom value")
+            .unwrap();
+        image.write_data(&[0]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        let value = reader
+            .decoder_mut()
+            .get_tag_ascii_string(Tag::Unknown(65000))
+            .unwrap();
+        assert_eq!(value, "custom value");
+    }
+
+    #[test]
+    fn test_cogreader_is_georeferenced() {
+        let file = georeferenced_tempfile();
+        let mut reader = CogReader::new(file).unwrap();
+        assert!(reader.is_georeferenced().unwrap());
+
+        // Plain TIFF files without georeferencing tags can still be decoded
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray8>(2, 1, &[1, 2]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+        assert!(!reader.is_georeferenced().unwrap());
+        assert_eq!(reader.ndarray::<u8>().unwrap(), array![[[1, 2]]]);
+    }
+
+    #[test]
+    fn test_cogreader_bounds() {
+        let file = georeferenced_tempfile();
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.bounds().unwrap(), [10.0, 18.5, 12.0, 20.0]);
+
+        // Write a GeoTIFF file with a rotated ModelTransformationTag
+        let model_transformation: [f64; 16] = [
+            1.0, 0.5, 0.0, 100.0, 0.5, -1.0, 0.0, 200.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+        ];
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(2, 2).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::ModelTransformationTag, &model_transformation[..])
+            .unwrap();
+        image.write_data(&[0; 4]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.bounds().unwrap(), [100.0, 198.0, 103.0, 201.0]);
+    }
+
+    #[test]
+    fn test_cogreader_xy_coords_2d() {
+        let file = georeferenced_tempfile();
+        let mut reader = CogReader::new(file).unwrap();
+        let (x_coords, y_coords) = reader.xy_coords().unwrap();
+        let (x_grid, y_grid) = reader.xy_coords_2d().unwrap();
+        assert_eq!(x_grid.dim(), (3, 4));
+        assert_eq!(x_grid.row(1), x_coords);
+        assert_eq!(y_grid.column(2), y_coords);
+
+        // Write a GeoTIFF file with a rotated ModelTransformationTag
+        let model_transformation: [f64; 16] = [
+            1.0, 0.5, 0.0, 100.0, 0.5, -1.0, 0.0, 200.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+        ];
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(2, 2).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::ModelTransformationTag, &model_transformation[..])
+            .unwrap();
+        image.write_data(&[0; 4]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(
+            reader.transform().unwrap(),
+            AffineTransform::new(1.0, 0.5, 100.0, 0.5, -1.0, 200.0)
+        );
+        let (x_grid, y_grid) = reader.xy_coords_2d().unwrap();
+        assert_eq!(x_grid, array![[100.75, 101.75], [101.25, 102.25]]);
+        assert_eq!(y_grid, array![[199.75, 200.25], [198.75, 199.25]]);
+        assert!(reader.xy_coords().is_err());
+    }
+
+    #[test]
+    fn test_cogreader_transform_without_tiepoint() {
+        // Write a GeoTIFF file with a ModelPixelScaleTag but no ModelTiepointTag
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(1, 1).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::ModelPixelScaleTag, &[2.0, 3.0, 0.0][..])
+            .unwrap();
+        image.write_data(&[0]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(
+            reader.transform().unwrap(),
+            AffineTransform::new(2.0, 0.0, 0.0, 0.0, -3.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_cogreader_transform_flipped() {
+        // North-up image, with a negative y-scale that should not be negated again
+        let file = geotiff_tempfile(4, 3, &[0.5, -0.5, 0.0], &[0.0, 0.0, 0.0, 10.0, 20.0, 0.0]);
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(
+            reader.transform().unwrap(),
+            AffineTransform::new(0.5, 0.0, 10.0, 0.0, -0.5, 20.0)
+        );
+        assert_eq!(reader.bounds().unwrap(), [10.0, 18.5, 12.0, 20.0]);
+        let (_, y_coords) = reader.xy_coords().unwrap();
+        assert_eq!(y_coords, array![19.75, 19.25, 18.75]);
+
+        // North-up image, with the tiepoint at the center of the image
+        let file = geotiff_tempfile(4, 3, &[0.5, 0.5, 0.0], &[2.0, 1.0, 0.0, 11.0, 19.5, 0.0]);
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.bounds().unwrap(), [10.0, 18.5, 12.0, 20.0]);
+
+        // Write a south-up GeoTIFF file with a positive y-resolution
+        let model_transformation: [f64; 16] = [
+            0.5, 0.0, 0.0, 10.0, 0.0, 0.5, 0.0, 18.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+        ];
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(4, 3).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::ModelTransformationTag, &model_transformation[..])
+            .unwrap();
+        image.write_data(&[0; 12]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.bounds().unwrap(), [10.0, 18.5, 12.0, 20.0]);
+        let (_, y_coords) = reader.xy_coords().unwrap();
+        assert_eq!(y_coords, array![18.75, 19.25, 19.75]);
+        let (_, y_coords) = reader.xy_coords_sorted().unwrap();
+        assert_eq!(y_coords, array![18.75, 19.25, 19.75]);
+    }
+
+    #[test]
+    fn test_cogreader_transform_pixel_scale_is_north_up() {
+        // A negative y-scale in the ModelPixelScaleTag could be meant as a south-up image
+        // extending upwards from the tiepoint, but its sign is ignored like GDAL does by
+        // default, so the image is read as north-up, extending downwards instead
+        let file = geotiff_tempfile(4, 3, &[0.5, -0.5, 0.0], &[0.0, 0.0, 0.0, 10.0, 18.5, 0.0]);
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(
+            reader.transform().unwrap(),
+            AffineTransform::new(0.5, 0.0, 10.0, 0.0, -0.5, 18.5)
+        );
+        assert_eq!(reader.bounds().unwrap(), [10.0, 17.0, 12.0, 18.5]);
+    }
+
+    /// Stream that counts the number of reads from the underlying stream
+    struct CountingReader<R> {
+        inner: R,
+        num_reads: Rc<Cell<usize>>,
+    }
+
+    impl<R: Read> Read for CountingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.num_reads.set(self.num_reads.get() + 1);
+            self.inner.read(buf)
+        }
+    }
+
+    impl<R: Seek> Seek for CountingReader<R> {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn test_cogreader_metadata_cache() {
+        let file = georeferenced_tempfile();
+        let num_reads: Rc<Cell<usize>> = Rc::new(Cell::new(0));
+        let stream = CountingReader {
+            inner: file,
+            num_reads: Rc::clone(&num_reads),
+        };
+        let mut reader = CogReader::new(stream).unwrap();
+
+        let transform = reader.transform().unwrap();
+        let nodata = reader.nodata().unwrap();
+        let epsg_code = reader.epsg_code().unwrap();
+        let num_samples = reader.num_samples().unwrap();
+        let dimensions = reader.dimensions().unwrap();
+        let num_reads_after_first_access: usize = num_reads.get();
+
+        // Tags are only read from the stream once
+        assert_eq!(reader.num_samples().unwrap(), num_samples);
+        assert_eq!(reader.dimensions().unwrap(), dimensions);
+        assert_eq!(reader.z_transform().unwrap(), None);
+        assert!(reader.is_georeferenced().unwrap());
+        assert_eq!(reader.transform().unwrap(), transform);
+        assert_eq!(reader.xy_coords().unwrap().0.len(), 4);
+        assert_eq!(reader.bounds().unwrap(), [10.0, 18.5, 12.0, 20.0]);
+        assert_eq!(reader.resolution().unwrap(), (0.5, 0.5));
+        assert_eq!(reader.nodata().unwrap(), nodata);
+        assert_eq!(reader.epsg_code().unwrap(), epsg_code);
+        assert_eq!(num_reads.get(), num_reads_after_first_access);
+    }
+
+    #[test]
+    fn test_cogreader_pixel_to_world_to_pixel() {
+        let file = georeferenced_tempfile();
+        let mut reader = CogReader::new(file).unwrap();
+
+        let (x, y) = reader.pixel_to_world(2.0, 3.0).unwrap();
+        assert_eq!((x, y), (11.5, 19.0));
+
+        let (row, col) = reader.world_to_pixel(x, y).unwrap();
+        assert_eq!((row, col), (2.0, 3.0));
+    }
+
+    #[test]
+    fn test_cogreader_read_image_2d() {
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray8>(2, 1, &[1, 2]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.read_image_2d::<u8>().unwrap(), array![[1, 2]]);
+
+        // Multi-band images cannot be read as a 2D array
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::RGB8>(1, 1, &[0, 0, 0])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+        assert!(reader.read_image_2d::<u8>().is_err());
+    }
+
+    #[test]
+    fn test_cogreader_xy_coords_sorted() {
+        let file = georeferenced_tempfile();
+        let mut reader = CogReader::new(file).unwrap();
+
+        let (x_coords, y_coords) = reader.xy_coords().unwrap();
+        assert_eq!(x_coords, array![10.25, 10.75, 11.25, 11.75]);
+        assert_eq!(y_coords, array![19.75, 19.25, 18.75]);
+
+        let (x_coords, y_coords) = reader.xy_coords_sorted().unwrap();
+        assert_eq!(x_coords, array![10.25, 10.75, 11.25, 11.75]);
+        assert_eq!(y_coords, array![18.75, 19.25, 19.75]);
+    }
+
+    #[test]
+    fn test_cogreader_resolution_is_geographic() {
+        let file = georeferenced_tempfile();
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.resolution().unwrap(), (0.5, 0.5));
+        assert!(!reader.is_geographic().unwrap());
+
+        // Write a GeoTIFF file with a geographic GTModelTypeGeoKey
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(1, 1).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::GeoKeyDirectoryTag, &[1u16, 1, 0, 1, 1024, 0, 1, 2][..])
+            .unwrap();
+        image.write_data(&[0]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert!(reader.is_geographic().unwrap());
+    }
+
+    #[test]
+    fn test_cogreader_read_geo_array() {
+        let file = georeferenced_tempfile();
+        let mut reader = CogReader::new(file).unwrap();
+        let geo_array = reader.read_geo_array::<f32>().unwrap();
+        assert_eq!(geo_array.data.dim(), (1, 3, 4));
+        assert_eq!(geo_array.x, array![10.25, 10.75, 11.25, 11.75]);
+        assert_eq!(geo_array.y, array![19.75, 19.25, 18.75]);
+        assert_eq!(
+            geo_array.transform,
+            AffineTransform::new(0.5, 0.0, 10.0, 0.0, -0.5, 20.0)
+        );
+        assert_eq!(geo_array.epsg, None);
+
+        // Write a GeoTIFF file with a user-defined ProjectedCSTypeGeoKey and a
+        // GeographicTypeGeoKey of WGS 84
+        let geo_keys: [u16; 12] = [1, 1, 0, 2, 2048, 0, 1, 4326, 3072, 0, 1, 32767];
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(1, 1).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::GeoKeyDirectoryTag, &geo_keys[..])
+            .unwrap();
+        image.write_data(&[0]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.epsg_code().unwrap(), Some(4326));
+    }
+
+    #[test]
+    fn test_cogreader_crs_citation() {
+        // Write a GeoTIFF file with PCSCitationGeoKey and GeogCitationGeoKey, but without
+        // GTCitationGeoKey
+        let ascii_params: &str = "WGS 84|Custom Transverse Mercator|";
+        let geo_keys: [u16; 12] = [1, 1, 0, 2, 2049, 34737, 7, 0, 3073, 34737, 27, 7];
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(1, 1).unwrap();
+        let encoder = image.encoder();
+        encoder
+            .write_tag(Tag::GeoKeyDirectoryTag, &geo_keys[..])
+            .unwrap();
+        encoder
+            .write_tag(Tag::GeoAsciiParamsTag, ascii_params)
+            .unwrap();
+        image.write_data(&[0]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(
+            reader.crs_citation().unwrap(),
+            Some("Custom Transverse Mercator".to_string())
+        );
+    }
+
+    #[test]
+    fn test_cogreader_rpc() {
+        // Write a TIFF file with an RPCCoefficientTag holding the values 0 to 91
+        let values: Vec<f64> = (0..92).map(f64::from).collect();
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(1, 1).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::from_u16_exhaustive(50844), &values[..])
+            .unwrap();
+        image.write_data(&[0]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        let rpc = reader.rpc().unwrap().unwrap();
+        assert_eq!(rpc.err_bias, 0.0);
+        assert_eq!(rpc.line_off, 2.0);
+        assert_eq!(rpc.height_scale, 11.0);
+        assert_eq!(rpc.line_num[0], 12.0);
+        assert_eq!(rpc.line_den[0], 32.0);
+        assert_eq!(rpc.samp_num[0], 52.0);
+        assert_eq!(rpc.samp_den[19], 91.0);
+    }
+
+    #[test]
+    fn test_cogreader_z_transform() {
+        let file = geotiff_tempfile(4, 3, &[0.5, 0.5, 0.1], &[0.0, 0.0, 0.0, 10.0, 20.0, -5.0]);
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.z_transform().unwrap(), Some((0.1, -5.0)));
+
+        let file = georeferenced_tempfile();
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.z_transform().unwrap(), None);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_cogreader_from_path_mmap() {
+        let mut named_file = tempfile::NamedTempFile::new().unwrap();
+        let mut tiff = TiffEncoder::new(&mut named_file).unwrap();
+        tiff.write_image::<colortype::Gray8>(2, 1, &[1, 2]).unwrap();
+
+        let mut reader = CogReader::from_path_mmap(named_file.path()).unwrap();
+        assert_eq!(reader.ndarray::<u8>().unwrap(), array![[[1, 2]]]);
+    }
+
+    #[test]
+    fn test_cogreader_from_slice() {
+        let mut buffer: Cursor<Vec<u8>> = Cursor::new(vec![]);
+        let mut tiff = TiffEncoder::new(&mut buffer).unwrap();
+        tiff.write_image::<colortype::Gray8>(2, 1, &[1, 2]).unwrap();
+
+        let mut reader = CogReader::from_slice(buffer.get_ref()).unwrap();
+        assert_eq!(reader.ndarray::<u8>().unwrap(), array![[[1, 2]]]);
+    }
+
+    #[test]
+    fn test_cogreader_tiles() {
+        // Write a TIFF file with 3 strips of height 4, 4 and 2
+        let image_data: Vec<f32> = (0..200).map(|v| v as f32).collect();
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray32Float>(20, 10).unwrap();
+        image.rows_per_strip(4).unwrap();
+        image.write_data(&image_data).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        let tiles: Vec<(TilePos, Array3<f32>)> =
+            reader.tiles::<f32>().collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(tiles.len(), 3);
+        assert_eq!(reader.tile_size().unwrap(), None);
+        assert_eq!(reader.rows_per_strip().unwrap(), Some(4));
+        let (last_pos, last_tile) = &tiles[2];
+        assert_eq!(
+            *last_pos,
+            TilePos {
+                col_off: 0,
+                row_off: 8,
+                width: 20,
+                height: 2
+            }
+        );
+        assert_eq!(last_tile.dim(), (1, 2, 20));
+        assert_eq!(last_tile[[0, 0, 0]], 160.0);
+        assert_eq!(last_tile[[0, 1, 19]], 199.0);
+    }
+
+    #[test]
+    fn test_cogreader_select_image() {
+        // Write a TIFF file with two full-resolution images, and an overview in between
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray32Float>(4, 4, &[0.0; 16])
+            .unwrap();
+        let mut overview = tiff.new_image::<colortype::Gray32Float>(2, 2).unwrap();
+        overview
+            .encoder()
+            .write_tag(Tag::NewSubfileType, 1u32)
+            .unwrap();
+        overview.write_data(&[0.0; 4]).unwrap();
+        tiff.write_image::<colortype::Gray32Float>(3, 3, &[1.0; 9])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.num_images().unwrap(), 2);
+
+        reader.select_image(1).unwrap();
+        let array = reader.ndarray::<f32>().unwrap();
+        assert_eq!(array, Array3::from_elem((1, 3, 3), 1.0));
+
+        assert!(reader.select_image(2).is_err());
+    }
+
+    #[test]
+    fn test_cogreader_read_resampled() {
+        // Write a TIFF file with a 4x4 image and a 2x2 overview
+        let image_data: Vec<f32> = (0..16).map(|v| v as f32).collect();
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray32Float>(4, 4, &image_data)
+            .unwrap();
+        let mut overview = tiff.new_image::<colortype::Gray32Float>(2, 2).unwrap();
+        overview
+            .encoder()
+            .write_tag(Tag::NewSubfileType, 1u32)
+            .unwrap();
+        overview.write_data(&[100.0, 101.0, 102.0, 103.0]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+
+        // Overview is large enough for the output size
+        let array = reader
+            .read_resampled::<f32>(2, 2, Resampling::Nearest)
+            .unwrap();
+        assert_eq!(array, array![[[100.0, 101.0], [102.0, 103.0]]]);
+        let array = reader
+            .read_resampled::<f32>(1, 1, Resampling::Average)
+            .unwrap();
+        assert_eq!(array, array![[[101.5]]]);
+
+        // Overview is too narrow, so the full-resolution image is resampled
+        let array = reader
+            .read_resampled::<f32>(4, 2, Resampling::Average)
+            .unwrap();
+        assert_eq!(
+            array,
+            array![[[2.0, 3.0, 4.0, 5.0], [10.0, 11.0, 12.0, 13.0]]]
+        );
+        let array = reader
+            .read_resampled::<f32>(4, 2, Resampling::Bilinear)
+            .unwrap();
+        assert_eq!(
+            array,
+            array![[[2.0, 3.0, 4.0, 5.0], [10.0, 11.0, 12.0, 13.0]]]
+        );
+
+        // Selected image is unchanged
+        assert_eq!(reader.ndarray::<f32>().unwrap().dim(), (1, 4, 4));
+
+        // Read options are applied as with ndarray
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::RGB8>(2, 2, &[1, 2, 3].repeat(4))
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReaderBuilder::new()
+            .bands(vec![2, 0])
+            .order(Order::ColumnMajor)
+            .build(file)
+            .unwrap();
+        let array = reader
+            .read_resampled::<u8>(1, 1, Resampling::Average)
+            .unwrap();
+        assert_eq!(array, array![[[3]], [[1]]]);
+        assert!(array.t().is_standard_layout());
+    }
+
+    #[test]
+    fn test_cogreader_sample_format_bits_per_sample() {
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray32Float>(1, 1, &[0.0])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.sample_format().unwrap(), vec![SampleFormat::IEEEFP]);
+        assert_eq!(reader.bits_per_sample().unwrap(), vec![32]);
+
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::RGB16>(2, 1, &[0; 6]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.sample_format().unwrap(), vec![SampleFormat::Uint; 3]);
+        assert_eq!(reader.bits_per_sample().unwrap(), vec![16, 16, 16]);
+    }
+
+    #[test]
+    fn test_cogreader_read_image_with_progress() {
+        let tiles: Vec<Option<Vec<u8>>> = (0..4).map(|v| Some(vec![v; 256])).collect();
+        let file = tiled_tiff_tempfile(32, 32, 16, &tiles, &[0, 1, 2, 3]);
+        let mut reader = CogReader::new(file).unwrap();
+
+        let mut fractions: Vec<f32> = vec![];
+        let array = reader
+            .read_image_with_progress::<u8>(|fraction| fractions.push(fraction))
+            .unwrap();
+        assert_eq!(fractions, vec![0.25, 0.5, 0.75, 1.0]);
+        assert_eq!(array, reader.ndarray::<u8>().unwrap());
+    }
+
+    #[test]
+    fn test_cogreader_read_rows() {
+        // Write a stripped TIFF file with 3 rows per strip
+        let image_data: Vec<u8> = (0..80).collect();
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(8, 10).unwrap();
+        image.rows_per_strip(3).unwrap();
+        image.write_data(&image_data).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.rows_per_strip().unwrap(), Some(3));
+
+        // Rows spanning two strips
+        let array = reader.read_rows::<u8>(4, 3).unwrap();
+        assert_eq!(array.dim(), (1, 3, 8));
+        assert_eq!(array[[0, 0, 0]], 32);
+        assert_eq!(array[[0, 2, 7]], 55);
+
+        // Rows are clamped to the image height
+        let array = reader.read_rows::<u8>(8, 5).unwrap();
+        assert_eq!(array.dim(), (1, 2, 8));
+        assert_eq!(array[[0, 1, 7]], 79);
+
+        let err = reader.read_rows::<u8>(10, 1).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Start row 10 is out of bounds of image with height=10"));
+    }
+
+    #[test]
+    fn test_cogreader_missing_sample_format() {
+        // Write a uint16 TIFF file, replacing its SampleFormat tag by an unknown private tag
+        let mut file = stripped_tiff_tempfile(2, 1, 16, 1, 1, &[1, 0, 2, 1]);
+        let mut buffer: Vec<u8> = vec![];
+        file.read_to_end(&mut buffer).unwrap();
+        let entry_offset: usize = 8 + 2 + 10 * 12;
+        assert_eq!(buffer[entry_offset..entry_offset + 2], 339u16.to_le_bytes());
+        buffer[entry_offset..entry_offset + 2].copy_from_slice(&65000u16.to_le_bytes());
+
+        // Samples default to unsigned integers
+        let mut reader = CogReader::new(Cursor::new(buffer)).unwrap();
+        assert_eq!(reader.sample_format().unwrap(), vec![SampleFormat::Uint]);
+        assert_eq!(reader.ndarray::<u16>().unwrap(), array![[[1, 258]]]);
+
+        // Undefined sample format
+        let file = stripped_tiff_tempfile(2, 1, 16, 4, 1, &[1, 0, 2, 1]);
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.sample_format().unwrap(), vec![SampleFormat::Void]);
+        let err = reader.ndarray::<u16>().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Undefined sample format (SampleFormat=4) is not supported"));
+    }
+
+    #[test]
+    fn test_cogreader_truncated_image_data() {
+        // Write a 4x1 uint16 TIFF file whose single strip only holds 2 of the 4 samples
+        let file = stripped_tiff_tempfile(4, 1, 16, 1, 1, &[1, 0, 2, 0]);
+        let mut reader = CogReader::new(file).unwrap();
+        let err = reader.ndarray::<u16>().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("the file may be truncated or corrupt"));
+    }
+
+    #[test]
+    fn test_cogreader_predictor() {
+        // Horizontal differencing of uint16 values, wrapping around on overflow
+        let values: [u16; 4] = [10, 20, 25, 5];
+        let strip: Vec<u8> = [10u16, 10, 5, 5u16.wrapping_sub(25)]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let file = stripped_tiff_tempfile(4, 1, 16, 1, 2, &strip);
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.predictor().unwrap(), Predictor::Horizontal);
+        let array = reader.ndarray::<u16>().unwrap();
+        assert_eq!(array.as_slice().unwrap(), values);
+
+        // Floating point predictor, i.e. horizontal differencing of the float32 values'
+        // big-endian bytes, after grouping them by byte significance
+        let values: [f32; 4] = [1.0, 2.5, -3.0, 0.0];
+        let bytes: Vec<[u8; 4]> = values.iter().map(|v| v.to_be_bytes()).collect();
+        let grouped: Vec<u8> = (0..4)
+            .flat_map(|byte| bytes.iter().map(move |v| v[byte]))
+            .collect();
+        let strip: Vec<u8> = (0..grouped.len())
+            .map(|i| match i {
+                0 => grouped[0],
+                _ => grouped[i].wrapping_sub(grouped[i - 1]),
+            })
+            .collect();
+        let file = stripped_tiff_tempfile(4, 1, 32, 3, 3, &strip);
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.predictor().unwrap(), Predictor::FloatingPoint);
+        let array = reader.ndarray::<f32>().unwrap();
+        assert_eq!(array.as_slice().unwrap(), values);
+
+        // Unknown predictor values are rejected instead of decoding wrong pixel values
+        let file = stripped_tiff_tempfile(4, 1, 16, 1, 4, &[0; 8]);
+        assert!(CogReader::new(file).is_err());
+    }
+
+    #[test]
+    fn test_cogreader_read_with_mask() {
+        // Write a TIFF file with a NoData pixel, a valid pixel and a NaN pixel
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray32Float>(3, 1).unwrap();
+        image.encoder().write_tag(Tag::GdalNodata, "-999").unwrap();
+        image.write_data(&[-999.0, 1.0, f32::NAN]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        let (array, mask) = reader.read_with_mask::<f32>().unwrap();
+        assert_eq!(array.dim(), (1, 1, 3));
+        assert_eq!(mask, array![[false, true, false]]);
+    }
+
+    #[test]
+    fn test_cogreader_orientation() {
+        // Orientation values with the expected top-left origin array for stored pixels of
+        // [[0, 1, 2], [3, 4, 5]]
+        let cases: [(u16, Array3<u8>); 3] = [
+            (1, array![[[0, 1, 2], [3, 4, 5]]]),
+            (2, array![[[2, 1, 0], [5, 4, 3]]]),
+            (6, array![[[3, 0], [4, 1], [5, 2]]]),
+        ];
+        for (orientation, expected) in cases {
+            let mut file = tempfile().unwrap();
+            let mut tiff = TiffEncoder::new(&mut file).unwrap();
+            let mut image = tiff.new_image::<colortype::Gray8>(3, 2).unwrap();
+            image
+                .encoder()
+                .write_tag(Tag::Orientation, orientation)
+                .unwrap();
+            image.write_data(&[0, 1, 2, 3, 4, 5]).unwrap();
+            file.seek(SeekFrom::Start(0)).unwrap();
+
+            let mut reader = CogReader::new(file).unwrap();
+            assert_eq!(reader.orientation().unwrap(), orientation);
+            assert_eq!(reader.ndarray::<u8>().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_cogreader_orientation_readers() {
+        // Readers of parts of the image take them from the visual image, as ndarray does,
+        // for a 3x4 image stored in strips of one row
+        for orientation in 1..=8u16 {
+            let mut file = tempfile().unwrap();
+            let mut tiff = TiffEncoder::new(&mut file).unwrap();
+            let mut image = tiff.new_image::<colortype::Gray8>(3, 4).unwrap();
+            image
+                .encoder()
+                .write_tag(Tag::Orientation, orientation)
+                .unwrap();
+            image.rows_per_strip(1).unwrap();
+            image.write_data(&(0..12).collect::<Vec<u8>>()).unwrap();
+            file.seek(SeekFrom::Start(0)).unwrap();
+            let mut reader = CogReader::new(file).unwrap();
+
+            let array: Array3<u8> = reader.ndarray().unwrap();
+            let (height, width): (usize, usize) = (array.dim().1, array.dim().2);
+            assert_eq!(
+                reader.read_window::<u8>(1, 1, 2, 2).unwrap(),
+                array.slice(s![.., 1..3, 1..3])
+            );
+            assert_eq!(
+                reader.read_rows::<u8>(1, 2).unwrap(),
+                array.slice(s![.., 1..3, ..])
+            );
+            let rows: Vec<Array2<u8>> = reader.rows::<u8>().map(|row| row.unwrap()).collect();
+            assert_eq!(rows.len(), height);
+            for (row_index, row) in rows.iter().enumerate() {
+                assert_eq!(row, array.slice(s![.., row_index, ..]));
+            }
+            for tile in reader.tiles::<u8>() {
+                let (tile_pos, tile): (TilePos, Array3<u8>) = tile.unwrap();
+                let (row_off, col_off) = (tile_pos.row_off as usize, tile_pos.col_off as usize);
+                assert_eq!(
+                    tile,
+                    array.slice(s![
+                        ..,
+                        row_off..row_off + tile_pos.height as usize,
+                        col_off..col_off + tile_pos.width as usize
+                    ])
+                );
+            }
+            let mut buffer: Vec<u8> = vec![0; width * height];
+            reader.read_image_into(&mut buffer).unwrap();
+            assert_eq!(
+                buffer,
+                array
+                    .as_standard_layout()
+                    .iter()
+                    .copied()
+                    .collect::<Vec<u8>>()
+            );
+            assert_eq!(
+                reader.read_image_with_progress::<u8>(|_| {}).unwrap(),
+                array
+            );
+            assert_eq!(
+                reader
+                    .read_resampled::<u8>(width as u32, height as u32, Resampling::Nearest)
+                    .unwrap(),
+                array
+            );
+
+            // Readers of the stored tile grid or georeferencing reject flipped/rotated images
+            if orientation != 1 {
+                let err = reader.read_bbox::<u8>(0.0, -4.0, 3.0, 0.0).unwrap_err();
+                assert!(err.to_string().contains("flipped and/or rotated"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cogreader_read_internal_mask() {
+        // Write a TIFF file with a 3x2 image, its 2x1 overview, and the mask of the image
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray8>(3, 2, &[7; 6]).unwrap();
+        let mut overview = tiff.new_image::<colortype::Gray8>(2, 1).unwrap();
+        overview
+            .encoder()
+            .write_tag(Tag::NewSubfileType, 1u32)
+            .unwrap();
+        overview.write_data(&[7; 2]).unwrap();
+        let mut mask = tiff.new_image::<colortype::Gray8>(3, 2).unwrap();
+        mask.encoder().write_tag(Tag::NewSubfileType, 4u32).unwrap();
+        mask.write_data(&[0, 1, 255, 255, 0, 1]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(
+            reader.read_internal_mask().unwrap(),
+            Some(array![[0, 255, 255], [255, 0, 255]])
+        );
+        // Reader is still at the full resolution image
+        assert_eq!(
+            reader.ndarray::<u8>().unwrap(),
+            Array3::from_elem((1, 2, 3), 7)
+        );
+    }
+
+    #[test]
+    fn test_cogreader_read_preview() {
+        // Write a TIFF file with a 8x4 image and a 4x2 overview
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray8>(8, 4, &[10; 32])
+            .unwrap();
+        let mut overview = tiff.new_image::<colortype::Gray8>(4, 2).unwrap();
+        overview
+            .encoder()
+            .write_tag(Tag::NewSubfileType, 1u32)
+            .unwrap();
+        overview.write_data(&[20; 8]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+
+        // Overview matches the preview size
+        let array = reader.read_preview::<u8>(4).unwrap();
+        assert_eq!(array, Array3::from_elem((1, 2, 4), 20));
+
+        // Overview is downsampled to the preview size
+        let array = reader.read_preview::<u8>(2).unwrap();
+        assert_eq!(array, Array3::from_elem((1, 1, 2), 20));
+
+        // Overview is too small, so the full resolution image is downsampled
+        let array = reader.read_preview::<u8>(6).unwrap();
+        assert_eq!(array, Array3::from_elem((1, 3, 6), 10));
+
+        // Image fits within the preview size
+        let array = reader.read_preview::<u8>(512).unwrap();
+        assert_eq!(array, Array3::from_elem((1, 4, 8), 10));
+
+        // Image that fits within the preview size is still rotated by its Orientation
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(2, 1).unwrap();
+        image.encoder().write_tag(Tag::Orientation, 6u16).unwrap();
+        image.write_data(&[1, 2]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+        let array = reader.read_preview::<u8>(512).unwrap();
+        assert_eq!(array, array![[[1], [2]]]);
+
+        // Image larger than the preview size is downsampled in the orientation of the
+        // visual image, for stored rows of 0, 10, 20 and 30 that become columns from the right
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray8>(8, 4).unwrap();
+        image.encoder().write_tag(Tag::Orientation, 6u16).unwrap();
+        let image_data: Vec<u8> = (0..32).map(|v| v / 8 * 10).collect();
+        image.write_data(&image_data).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+        let array = reader.read_preview::<u8>(8).unwrap();
+        assert_eq!(array.dim(), (1, 8, 4));
+        assert_eq!(array.slice(s![0, 0, ..]), array![30, 20, 10, 0]);
+        let array = reader.read_preview::<u8>(4).unwrap();
+        assert_eq!(
+            array,
+            Array3::from_shape_fn((1, 4, 2), |(_, _, col)| [25, 5][col])
+        );
+    }
+
+    #[test]
+    fn test_cogreader_read_overview() {
+        // Write a TIFF file with a 4x4 image and 2x2 and 1x1 overviews
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray8>(4, 4, &[0; 16])
+            .unwrap();
+        for (size, value) in [(2, 1), (1, 2)] {
+            let mut overview = tiff.new_image::<colortype::Gray8>(size, size).unwrap();
+            overview
+                .encoder()
+                .write_tag(Tag::NewSubfileType, 1u32)
+                .unwrap();
+            overview
+                .write_data(&vec![value; (size * size) as usize])
+                .unwrap();
+        }
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.num_overviews().unwrap(), 2);
+        assert_eq!(
+            reader.read_overview::<u8>(0).unwrap(),
+            Array3::from_elem((1, 2, 2), 1)
+        );
+        assert_eq!(reader.read_overview::<u8>(1).unwrap(), array![[[2]]]);
+        let err = reader.read_overview::<u8>(2).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Overview level 2 does not exist, image has 2 overviews"));
+
+        // Reader is still at the full resolution image
+        assert_eq!(reader.ndarray::<u8>().unwrap().dim(), (1, 4, 4));
+    }
+
+    #[test]
+    fn test_cogreader_read_pyramid() {
+        // Write a GeoTIFF file with a 4x4 image and a 2x2 overview without geotags
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray32Float>(4, 4).unwrap();
+        let encoder = image.encoder();
+        encoder
+            .write_tag(Tag::ModelPixelScaleTag, &[10.0, 10.0, 0.0][..])
+            .unwrap();
+        encoder
+            .write_tag(
+                Tag::ModelTiepointTag,
+                &[0.0, 0.0, 0.0, 100.0, 200.0, 0.0][..],
+            )
+            .unwrap();
+        image.write_data(&[0.0; 16]).unwrap();
+        let mut overview = tiff.new_image::<colortype::Gray32Float>(2, 2).unwrap();
+        overview
+            .encoder()
+            .write_tag(Tag::NewSubfileType, 1u32)
+            .unwrap();
+        overview.write_data(&[1.0; 4]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        let pyramid = reader.read_pyramid::<f32>().unwrap();
+        assert_eq!(pyramid.len(), 2);
+
+        let (level0, transform0) = &pyramid[0];
+        assert_eq!(*level0, Array3::from_elem((1, 4, 4), 0.0));
+        assert_eq!(
+            *transform0,
+            AffineTransform::new(10.0, 0.0, 100.0, 0.0, -10.0, 200.0)
+        );
+        let (level1, transform1) = &pyramid[1];
+        assert_eq!(*level1, Array3::from_elem((1, 2, 2), 1.0));
+        assert_eq!(
+            *transform1,
+            AffineTransform::new(20.0, 0.0, 100.0, 0.0, -20.0, 200.0)
+        );
+    }
+
+    #[test]
+    fn test_cogreader_nodata() {
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray32Float>(2, 2).unwrap();
+        image.encoder().write_tag(Tag::GdalNodata, "-9999").unwrap();
+        image.write_data(&[-9999.0, 1.0, 2.0, 3.0]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.nodata().unwrap(), Some(-9999.0));
+    }
+
+    #[test]
+    fn test_cogreader_unsupported_compression() {
+        // Write an uncompressed TIFF file that claims to be LERC (34887) compressed
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray32Float>(2, 2).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::Compression, 34887u16)
+            .unwrap();
+        image.write_data(&[0.0, 1.0, 2.0, 3.0]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(
+            reader.compression().unwrap(),
+            CompressionMethod::Unknown(34887)
+        );
+        let err = reader.ndarray::<f32>().unwrap_err();
+        assert!(matches!(
+            &err,
+            TiffError::IoError(io_err) if io_err.kind() == io::ErrorKind::Unsupported
+        ));
+        assert!(err
+            .to_string()
+            .contains("LERC compression (method 34887) is not supported"));
+    }
+
+    #[test]
+    fn test_cogreader_unsupported_complex_sample_format() {
+        // Write a TIFF file with complex int16 (SampleFormat=5) samples
+        let file = stripped_tiff_tempfile(2, 1, 32, 5, 1, &[0; 8]);
+
+        let mut reader = CogReader::new(file).unwrap();
+        let err = reader.ndarray::<f32>().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Complex sample format (CInt16) is not supported"));
+        assert!(reader.read_window::<f32>(0, 0, 2, 1).is_err());
+    }
+
+    #[test]
+    fn test_cogreader_read_bbox() {
+        let file = georeferenced_tempfile();
+        let mut reader = CogReader::new(file).unwrap();
+
+        let (array, transform) = reader.read_bbox::<f32>(10.5, 19.0, 11.5, 19.5).unwrap();
+        assert_eq!(array, array![[[5.0, 6.0]]]);
+        assert_eq!(
             transform,
-            AffineTransform::new(200.0, 0.0, 499980.0, 0.0, -200.0, 5300040.0)
+            AffineTransform::new(0.5, 0.0, 10.5, 0.0, -0.5, 19.5)
+        );
+
+        assert!(reader.read_bbox::<f32>(0.0, 0.0, 1.0, 1.0).is_err());
+
+        // Windows whose end overflows u32 are out of bounds too
+        let err = reader.read_window::<f32>(u32::MAX, 0, 2, 1).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("is out of bounds of image with width=4"));
+        assert!(reader.read_window::<f32>(0, 1, 1, u32::MAX).is_err());
+    }
+
+    #[test]
+    fn test_cogreader_is_bigtiff() {
+        let mut file = tempfile().unwrap();
+        let mut bigtiff = TiffEncoder::new_big(&mut file).unwrap();
+        bigtiff
+            .write_image::<colortype::Gray32Float>(2, 2, &[0.0; 4])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let reader = CogReader::new(file).unwrap();
+        assert!(reader.is_bigtiff());
+
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray32Float>(2, 2, &[0.0; 4])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let reader = CogReader::new(file).unwrap();
+        assert!(!reader.is_bigtiff());
+    }
+
+    #[test]
+    fn test_cogreader_byte_order() {
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray8>(1, 1, &[0]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.byte_order(), ByteOrder::LittleEndian);
+
+        // Write a big-endian 1x1 uint8 TIFF file by hand, with IFD entries of
+        // (tag, field type (3=SHORT), count, value)
+        let mut buffer: Vec<u8> = vec![];
+        buffer.extend(b"MM");
+        buffer.extend(42u16.to_be_bytes());
+        buffer.extend(8u32.to_be_bytes());
+        let entries: [(u16, u16); 8] = [
+            (256, 1),              // ImageWidth
+            (257, 1),              // ImageLength
+            (258, 8),              // BitsPerSample
+            (259, 1),              // Compression
+            (262, 1),              // PhotometricInterpretation
+            (273, 8 + 2 + 96 + 4), // StripOffsets
+            (278, 1),              // RowsPerStrip
+            (279, 1),              // StripByteCounts
+        ];
+        buffer.extend(8u16.to_be_bytes());
+        for (tag, value) in entries {
+            buffer.extend(tag.to_be_bytes());
+            buffer.extend(3u16.to_be_bytes());
+            buffer.extend(1u32.to_be_bytes());
+            buffer.extend([value.to_be_bytes(), [0, 0]].concat());
+        }
+        buffer.extend(0u32.to_be_bytes()); // no next IFD
+        buffer.push(42); // pixel data
+
+        let mut reader = CogReader::new(Cursor::new(buffer)).unwrap();
+        assert_eq!(reader.byte_order(), ByteOrder::BigEndian);
+        assert_eq!(reader.ndarray::<u8>().unwrap(), array![[[42]]]);
+    }
+
+    #[test]
+    fn test_cogreader_ghost_header() {
+        // Write a 1x1 uint8 TIFF file by hand, with a ghost area between the header and the
+        // IFD, and IFD entries of (tag, field type (3=SHORT), count, value)
+        let metadata: &str = "LAYOUT=IFDS_BEFORE_DATA\nBLOCK_ORDER=ROW_MAJOR\n\
+                              BLOCK_LEADER=SIZE_AS_UINT4\nBLOCK_TRAILER=LAST_4_BYTES_REPEATED\n\
+                              KNOWN_INCOMPATIBLE_EDITION=NO\n";
+        let ghost_area: String = format!(
+            "GDAL_STRUCTURAL_METADATA_SIZE={:06} bytes\n{metadata}",
+            metadata.len()
+        );
+        let ifd_offset: u16 = 8 + ghost_area.len() as u16;
+        let mut buffer: Vec<u8> = vec![];
+        buffer.extend(b"II");
+        buffer.extend(42u16.to_le_bytes());
+        buffer.extend((ifd_offset as u32).to_le_bytes());
+        buffer.extend(ghost_area.as_bytes());
+        let entries: [(u16, u16); 8] = [
+            (256, 1),                       // ImageWidth
+            (257, 1),                       // ImageLength
+            (258, 8),                       // BitsPerSample
+            (259, 1),                       // Compression
+            (262, 1),                       // PhotometricInterpretation
+            (273, ifd_offset + 2 + 96 + 4), // StripOffsets
+            (278, 1),                       // RowsPerStrip
+            (279, 1),                       // StripByteCounts
+        ];
+        buffer.extend(8u16.to_le_bytes());
+        for (tag, value) in entries {
+            buffer.extend(tag.to_le_bytes());
+            buffer.extend(3u16.to_le_bytes());
+            buffer.extend(1u32.to_le_bytes());
+            buffer.extend([value.to_le_bytes(), [0, 0]].concat());
+        }
+        buffer.extend(0u32.to_le_bytes()); // no next IFD
+        buffer.push(42); // pixel data
+
+        let mut reader = CogReader::new(Cursor::new(buffer)).unwrap();
+        let ghost_header = reader.ghost_header().unwrap().unwrap();
+        assert!(ghost_header.ifds_before_data);
+        assert!(ghost_header.block_order_row_major);
+        assert!(ghost_header.block_leader_size_as_uint4);
+        assert!(ghost_header.block_trailer_last_4_bytes_repeated);
+        assert!(!ghost_header.mask_interleaved_with_imagery);
+        assert!(!ghost_header.known_incompatible_edition);
+        assert_eq!(
+            ghost_header.items[4],
+            (
+                String::from("KNOWN_INCOMPATIBLE_EDITION"),
+                String::from("NO")
+            )
+        );
+        assert_eq!(reader.ndarray::<u8>().unwrap(), array![[[42]]]);
+    }
+
+    #[tokio::test]
+    async fn test_read_geotiff_async() {
+        let cog_url: &str =
+            "https://github.com/cogeotiff/rio-tiler/raw/6.4.0/tests/fixtures/cog_nodata_nan.tif";
+        let tif_url = Url::parse(cog_url).unwrap();
+        let (store, location) = parse_url(&tif_url).unwrap();
+
+        let array = read_geotiff_async::<f32>(&store, &location).await.unwrap();
+        assert_eq!(array.dim(), (1, 549, 549));
+        assert_eq!(array[[0, 500, 500]], 0.13482364);
+    }
+
+    #[test]
+    fn test_cogreader_color_type() {
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray16>(1, 1, &[0]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.color_type().unwrap(), ColorType::Gray(16));
+    }
+
+    #[test]
+    fn test_cogreader_decoded_size() {
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::RGB16>(5, 2, &[0; 30])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.decoded_size().unwrap(), 3 * 5 * 2 * 2);
+    }
+
+    #[test]
+    fn test_cogreader_read_image_into() {
+        // Write a TIFF file with 3 strips of height 4, 4 and 2
+        let image_data: Vec<f32> = (0..200).map(|v| v as f32).collect();
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray32Float>(20, 10).unwrap();
+        image.rows_per_strip(4).unwrap();
+        image.write_data(&image_data).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file.try_clone().unwrap()).unwrap();
+        let mut buffer: Vec<f32> = vec![0.0; 200];
+        reader.read_image_into(&mut buffer).unwrap();
+        assert_eq!(buffer, image_data);
+
+        let mut wrong_size_buffer: Vec<f32> = vec![0.0; 100];
+        assert!(reader.read_image_into(&mut wrong_size_buffer).is_err());
+
+        // Read options that change the decoded bands or values are rejected
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReaderBuilder::new().bands(vec![0]).build(file).unwrap();
+        let err = reader.read_image_into(&mut buffer).unwrap_err();
+        assert!(err.to_string().contains("use ndarray instead"));
+    }
+
+    #[test]
+    fn test_cogreader_read_mask() {
+        // Write an RGBA TIFF file with an unassociated alpha band
+        let image_data: Vec<u8> = vec![10, 20, 30, 255, 40, 50, 60, 0, 70, 80, 90, 128];
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::RGBA8>(3, 1).unwrap();
+        image.encoder().write_tag(Tag::ExtraSamples, 2u16).unwrap();
+        image.write_data(&image_data).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.alpha_band_index().unwrap(), Some(3));
+        assert_eq!(reader.read_mask().unwrap(), Some(array![[255, 0, 255]]));
+    }
+
+    #[test]
+    fn test_read_geotiff_sparse_tiles() {
+        // Write a TIFF file with two 16x16 tiles, where the second one is sparse
+        let tiles = [Some(vec![7u8; 256]), None];
+        let file = tiled_tiff_tempfile(32, 16, 16, &tiles, &[0, 1]);
+
+        let array = read_geotiff::<u8, _>(file).unwrap();
+        assert_eq!(array.dim(), (1, 16, 32));
+        assert!(array.slice(s![.., .., 0..16]).iter().all(|v| *v == 7));
+        assert!(array.slice(s![.., .., 16..32]).iter().all(|v| *v == 0));
+    }
+
+    #[test]
+    fn test_cogreader_tiles_out_of_order() {
+        // Write a TIFF file with a 2x2 grid of 16x16 tiles, stored in shuffled order
+        let tiles: Vec<Option<Vec<u8>>> = (0..4).map(|v| Some(vec![v; 256])).collect();
+        let file = tiled_tiff_tempfile(32, 32, 16, &tiles, &[2, 0, 3, 1]);
+        let mut reader = CogReader::new(file).unwrap();
+
+        let tile_offsets = reader.tile_offsets().unwrap();
+        let offsets: Vec<u64> = tile_offsets.iter().map(|(_, offset, _)| *offset).collect();
+        assert!(offsets[2] < offsets[0] && offsets[0] < offsets[3] && offsets[3] < offsets[1]);
+        assert_eq!(
+            tile_offsets[1].0,
+            TilePos {
+                col_off: 16,
+                row_off: 0,
+                width: 16,
+                height: 16
+            }
+        );
+        assert!(tile_offsets
+            .iter()
+            .all(|(_, _, byte_count)| *byte_count == 256));
+
+        // Each tile lands at the position of its index in the TileOffsets tag
+        for (index, tile) in reader.tiles::<u8>().enumerate() {
+            let (tile_pos, array) = tile.unwrap();
+            assert_eq!(tile_pos, tile_offsets[index].0);
+            assert!(array.iter().all(|v| *v == index as u8));
+        }
+        let array = reader.ndarray::<u8>().unwrap();
+        assert_eq!(array[[0, 0, 0]], 0);
+        assert_eq!(array[[0, 0, 16]], 1);
+        assert_eq!(array[[0, 16, 0]], 2);
+        assert_eq!(array[[0, 16, 16]], 3);
+    }
+
+    #[test]
+    fn test_cogreader_to_zarr_chunk_spec() {
+        // Write a TIFF file with a 2x2 grid of 16x16 tiles, with the second one sparse
+        let tiles: Vec<Option<Vec<u8>>> = vec![
+            Some(vec![0; 256]),
+            None,
+            Some(vec![2; 256]),
+            Some(vec![3; 256]),
+        ];
+        let file = tiled_tiff_tempfile(32, 32, 16, &tiles, &[3, 0, 2, 1]);
+        let mut reader = CogReader::new(file).unwrap();
+        let tile_offsets = reader.tile_offsets().unwrap();
+
+        let spec = reader.to_zarr_chunk_spec().unwrap();
+        assert_eq!(spec.shape, [32, 32, 1]);
+        assert_eq!(spec.chunk_shape, [16, 16, 1]);
+        assert_eq!(spec.dtype, "|u1");
+        assert_eq!(spec.fill_value, None);
+        assert_eq!(spec.compression, CompressionMethod::None);
+        assert_eq!(spec.predictor, Predictor::None);
+        assert_eq!(spec.chunks.len(), 3);
+        assert_eq!(
+            spec.chunks[1],
+            ZarrChunkRef {
+                index: [1, 0, 0],
+                offset: tile_offsets[2].1,
+                length: 256
+            }
+        );
+    }
+
+    #[test]
+    fn test_cogreader_verify_tile_checksums() {
+        let tiles: Vec<Option<Vec<u8>>> = vec![Some(vec![0; 256]), None, Some(vec![1; 256])];
+        let file = tiled_tiff_tempfile(48, 16, 16, &tiles, &[0, 1, 2]);
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(
+            reader.verify_tile_checksums().unwrap(),
+            TileChecksums::NotAvailable(vec![])
+        );
+
+        // Last tile is truncated
+        let tiles: Vec<Option<Vec<u8>>> = vec![Some(vec![0; 256]), Some(vec![1; 100])];
+        let file = tiled_tiff_tempfile(32, 16, 16, &tiles, &[0, 1]);
+        let mut reader = CogReader::new(file).unwrap();
+        let TileChecksums::NotAvailable(failures) = reader.verify_tile_checksums().unwrap() else {
+            panic!("File without a ghost header should have no checksums");
+        };
+        assert_eq!(failures.len(), 1);
+        let (tile_pos, reason) = &failures[0];
+        assert_eq!(tile_pos.col_off, 16);
+        assert_eq!(
+            reason,
+            "Byte count 100 is less than the 256 bytes of uncompressed data"
+        );
+
+        // Write a 4x1 uint8 TIFF file by hand, with a ghost area declaring block trailers,
+        // and IFD entries of (tag, field type (3=SHORT), count, value)
+        let tiff_with_trailer = |trailer: [u8; 4]| -> Vec<u8> {
+            let metadata: &str = "BLOCK_TRAILER=LAST_4_BYTES_REPEATED\n\
+                                  KNOWN_INCOMPATIBLE_EDITION=NO\n";
+            let ghost_area: String = format!(
+                "GDAL_STRUCTURAL_METADATA_SIZE={:06} bytes\n{metadata}",
+                metadata.len()
+            );
+            let ifd_offset: u16 = 8 + ghost_area.len() as u16;
+            let mut buffer: Vec<u8> = vec![];
+            buffer.extend(b"II");
+            buffer.extend(42u16.to_le_bytes());
+            buffer.extend((ifd_offset as u32).to_le_bytes());
+            buffer.extend(ghost_area.as_bytes());
+            let entries: [(u16, u16); 8] = [
+                (256, 4),                       // ImageWidth
+                (257, 1),                       // ImageLength
+                (258, 8),                       // BitsPerSample
+                (259, 1),                       // Compression
+                (262, 1),                       // PhotometricInterpretation
+                (273, ifd_offset + 2 + 96 + 4), // StripOffsets
+                (278, 1),                       // RowsPerStrip
+                (279, 4),                       // StripByteCounts
+            ];
+            buffer.extend(8u16.to_le_bytes());
+            for (tag, value) in entries {
+                buffer.extend(tag.to_le_bytes());
+                buffer.extend(3u16.to_le_bytes());
+                buffer.extend(1u32.to_le_bytes());
+                buffer.extend([value.to_le_bytes(), [0, 0]].concat());
+            }
+            buffer.extend(0u32.to_le_bytes()); // no next IFD
+            buffer.extend([1, 2, 3, 4]); // pixel data
+            buffer.extend(trailer);
+            buffer
+        };
+
+        let mut reader = CogReader::new(Cursor::new(tiff_with_trailer([1, 2, 3, 4]))).unwrap();
+        assert_eq!(
+            reader.verify_tile_checksums().unwrap(),
+            TileChecksums::Verified(vec![])
+        );
+
+        let mut reader = CogReader::new(Cursor::new(tiff_with_trailer([1, 2, 3, 5]))).unwrap();
+        let TileChecksums::Verified(failures) = reader.verify_tile_checksums().unwrap() else {
+            panic!("File with block trailers should have checksums");
+        };
+        assert_eq!(failures.len(), 1);
+        assert_eq!(
+            failures[0].1,
+            "Block trailer [1, 2, 3, 5] does not match the last 4 bytes [1, 2, 3, 4] of the tile"
+        );
+    }
+
+    #[test]
+    fn test_cogreader_partial_edge_tiles() {
+        // Write a 100x100 TIFF file with 64x64 tiles, padded beyond the image extent
+        let tiles: Vec<Option<Vec<u8>>> = (0..4).map(|v| Some(vec![v; 64 * 64])).collect();
+        let file = tiled_tiff_tempfile(100, 100, 64, &tiles, &[0, 1, 2, 3]);
+        let mut reader = CogReader::new(file).unwrap();
+
+        let array = reader.ndarray::<u8>().unwrap();
+        assert_eq!(array.dim(), (1, 100, 100));
+        assert_eq!(array[[0, 63, 63]], 0);
+        assert_eq!(array[[0, 0, 99]], 1);
+        assert_eq!(array[[0, 99, 0]], 2);
+        assert_eq!(array[[0, 99, 99]], 3);
+
+        // Edge tiles are clipped to the image extent
+        let tile_sizes: Vec<(u32, u32)> = reader
+            .tiles::<u8>()
+            .map(|tile| {
+                let (tile_pos, array) = tile.unwrap();
+                assert_eq!(
+                    array.dim(),
+                    (1, tile_pos.height as usize, tile_pos.width as usize)
+                );
+                (tile_pos.width, tile_pos.height)
+            })
+            .collect();
+        assert_eq!(tile_sizes, vec![(64, 64), (36, 64), (64, 36), (36, 36)]);
+
+        let window = reader.read_window::<u8>(60, 60, 40, 40).unwrap();
+        assert_eq!(window.dim(), (1, 40, 40));
+        assert_eq!(window[[0, 39, 39]], 3);
+        let mut buffer: Vec<u8> = vec![0; 100 * 100];
+        reader.read_image_into(&mut buffer).unwrap();
+        assert_eq!(buffer, array.into_raw_vec());
+    }
+
+    #[test]
+    fn test_cogreader_rows() {
+        // Tiled image, with rows of tiles buffered
+        let tiles: Vec<Option<Vec<u8>>> = (0..4).map(|v| Some(vec![v; 64 * 64])).collect();
+        let file = tiled_tiff_tempfile(100, 100, 64, &tiles, &[0, 1, 2, 3]);
+        let mut reader = CogReader::new(file).unwrap();
+        let array = reader.ndarray::<u8>().unwrap();
+        let rows: Vec<Array2<u8>> = reader.rows::<u8>().map(|row| row.unwrap()).collect();
+        assert_eq!(rows.len(), 100);
+        assert_eq!(rows[0].dim(), (1, 100));
+        assert_eq!(rows[70], array.slice(s![.., 70, ..]));
+
+        // Stripped image
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray8>(2, 3, &[0, 1, 2, 3, 4, 5])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+        let rows: Vec<Array2<u8>> = reader.rows::<u8>().map(|row| row.unwrap()).collect();
+        assert_eq!(rows, vec![array![[0, 1]], array![[2, 3]], array![[4, 5]]]);
+    }
+
+    #[test]
+    fn test_cogreader_read_tile_range() {
+        // Write a 100x100 TIFF file with a 2x2 grid of 64x64 tiles
+        let tiles: Vec<Option<Vec<u8>>> = (0..4).map(|v| Some(vec![v; 64 * 64])).collect();
+        let file = tiled_tiff_tempfile(100, 100, 64, &tiles, &[0, 1, 2, 3]);
+        let mut reader = CogReader::new(file).unwrap();
+
+        let array = reader.read_tile_range::<u8>(0..2, 0..2).unwrap();
+        assert_eq!(array, reader.ndarray::<u8>().unwrap());
+
+        let array = reader.read_tile_range::<u8>(1..2, 0..2).unwrap();
+        assert_eq!(array.dim(), (1, 100, 36));
+        assert_eq!(array[[0, 0, 0]], 1);
+        assert_eq!(array[[0, 99, 35]], 3);
+
+        let err = reader.read_tile_range::<u8>(0..3, 0..1).unwrap_err();
+        assert!(err.to_string().contains(
+            "Tile range (cols=0..3, rows=0..1) is empty or out of range of tile grid with 2 \
+             columns and 2 rows"
+        ));
+        let empty_range: Range<u32> = Range { start: 1, end: 1 };
+        assert!(reader.read_tile_range::<u8>(empty_range, 0..1).is_err());
+    }
+
+    #[test]
+    fn test_cogreader_read_tile() {
+        // Write a TIFF file with a 2x2 grid of 16x16 tiles, stored in reverse order
+        let tiles: Vec<Option<Vec<u8>>> = (0..4).map(|v| Some(vec![v; 256])).collect();
+        let file = tiled_tiff_tempfile(32, 32, 16, &tiles, &[3, 2, 1, 0]);
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.tile_size().unwrap(), Some((16, 16)));
+        assert_eq!(reader.rows_per_strip().unwrap(), None);
+        let tile: Array3<u8> = reader.read_tile(0, 1).unwrap();
+        assert_eq!(tile.dim(), (1, 16, 16));
+        assert!(tile.iter().all(|v| *v == 2));
+
+        assert!(reader.read_tile::<u8>(2, 0).is_err());
+
+        // Stripped images cannot be read by tile
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray8>(1, 1, &[0]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(reader.tile_size().unwrap(), None);
+        assert!(reader.read_tile::<u8>(0, 0).is_err());
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_read_arrow() {
+        use arrow_array::{Array, Float32Array, UInt16Array, UInt8Array};
+
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray32Float>(2, 1, &[0.5, 11.0])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let (array, shape) = crate::io::geotiff::read_arrow(file).unwrap();
+        assert_eq!(shape, (1, 1, 2));
+        let array = array.as_any().downcast_ref::<Float32Array>().unwrap();
+        assert_eq!(array.values().to_vec(), vec![0.5, 11.0]);
+
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray16>(2, 1, &[1, 65535])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let (array, shape) = crate::io::geotiff::read_arrow(file).unwrap();
+        assert_eq!(shape, (1, 1, 2));
+        let array = array.as_any().downcast_ref::<UInt16Array>().unwrap();
+        assert_eq!(array.values().to_vec(), vec![1, 65535]);
+
+        // Sparse images keep the dtype of the file
+        let tiles = [Some(vec![7u8; 256]), None];
+        let file = tiled_tiff_tempfile(32, 16, 16, &tiles, &[0, 1]);
+        let (array, shape) = crate::io::geotiff::read_arrow(file).unwrap();
+        assert_eq!(shape, (1, 16, 32));
+        let array = array.as_any().downcast_ref::<UInt8Array>().unwrap();
+        assert_eq!(array.len(), 512);
+        assert_eq!(array.value(15), 7);
+        assert_eq!(array.value(16), 0);
+    }
+
+    #[test]
+    fn test_cogreader_read_raw() {
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray32Float>(2, 1, &[0.5, 11.0])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+        let (bytes, dtype, shape) = reader.read_raw().unwrap();
+        assert_eq!(dtype, DataType::Float32);
+        assert_eq!(shape, (1, 1, 2));
+        assert_eq!(bytes.len(), 2 * dtype.size());
+        assert_eq!(bytes[4..8], 11.0f32.to_le_bytes());
+
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray16>(2, 1, &[1, 65535])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+        let (bytes, dtype, shape) = reader.read_raw().unwrap();
+        assert_eq!(dtype, DataType::UInt16);
+        assert_eq!(shape, (1, 1, 2));
+        assert_eq!(bytes, vec![1, 0, 255, 255]);
+
+        // Rotated images (Orientation=6) have the shape of the visual image
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray16>(2, 1).unwrap();
+        image.encoder().write_tag(Tag::Orientation, 6u16).unwrap();
+        image.write_data(&[1, 65535]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = CogReader::new(file).unwrap();
+        let (bytes, dtype, shape) = reader.read_raw().unwrap();
+        assert_eq!(dtype, DataType::UInt16);
+        assert_eq!(shape, (1, 2, 1));
+        assert_eq!(bytes, vec![1, 0, 255, 255]);
+
+        // Sparse images keep the dtype of the file
+        let tiles = [Some(vec![7u8; 256]), None];
+        let file = tiled_tiff_tempfile(32, 16, 16, &tiles, &[0, 1]);
+        let mut reader = CogReader::new(file).unwrap();
+        let (bytes, dtype, shape) = reader.read_raw().unwrap();
+        assert_eq!(dtype, DataType::UInt8);
+        assert_eq!(shape, (1, 16, 32));
+        assert_eq!(bytes[..32], [[7; 16], [0; 16]].concat());
+    }
+
+    #[test]
+    fn test_read_geotiff_fortran() {
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray8>(3, 2, &[0, 1, 2, 3, 4, 5])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let array = read_geotiff_fortran::<u8, _>(file).unwrap();
+        assert_eq!(array, array![[[0, 1, 2], [3, 4, 5]]]);
+        assert!(!array.is_standard_layout());
+        assert!(array.t().is_standard_layout());
+        assert_eq!(array.as_slice_memory_order().unwrap(), [0, 3, 1, 4, 2, 5]);
+    }
+
+    #[test]
+    fn test_read_geotiff_as() {
+        // Write a uint16 TIFF file with a scale and offset in the GDAL metadata
+        let xml: &str = r#"<GDALMetadata>
+  <Item name="SCALE" sample="0" role="scale">0.5</Item>
+  <Item name="OFFSET" sample="0" role="offset">10</Item>
+</GDALMetadata>"#;
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::Gray16>(3, 1).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::from_u16_exhaustive(42112), xml)
+            .unwrap();
+        image.write_data(&[0, 1, 65535]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let array = read_geotiff_as::<f32, _>(file).unwrap();
+        assert_eq!(array, array![[[10.0, 10.5, 32777.5]]]);
+
+        // Values without scale and offset are cast directly
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray32Float>(2, 1, &[1.5, 11.0])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let array = read_geotiff_as::<u8, _>(file).unwrap();
+        assert_eq!(array, array![[[1, 11]]]);
+    }
+
+    #[test]
+    fn test_cogreader_pool() {
+        let mut file =
+            geotiff_tempfile(64, 32, &[0.5, 0.5, 0.0], &[0.0, 0.0, 0.0, 10.0, 20.0, 0.0]);
+        let mut buffer: Vec<u8> = vec![];
+        file.read_to_end(&mut buffer).unwrap();
+        let pool = CogReaderPool::new(Bytes::from(buffer)).unwrap();
+        assert_eq!(pool.num_idle(), 1);
+
+        // Read many windows concurrently from different threads
+        (0..1000u32).into_par_iter().for_each(|i| {
+            let (col_off, row_off): (u32, u32) = (i % 60, i % 28);
+            let mut reader = pool.get().unwrap();
+            let array = reader.read_window::<f32>(col_off, row_off, 4, 4).unwrap();
+            assert_eq!(array.dim(), (1, 4, 4));
+            assert_eq!(array[[0, 0, 0]], (row_off * 64 + col_off) as f32);
+        });
+
+        // Readers are reused, so at most one reader is created per thread
+        let num_idle: usize = pool.num_idle();
+        assert!((1..=rayon::current_num_threads()).contains(&num_idle));
+
+        // Readers are returned to the pool after use
+        {
+            let _reader = pool.get().unwrap();
+            assert_eq!(pool.num_idle(), num_idle - 1);
+        }
+        assert_eq!(pool.num_idle(), num_idle);
+
+        // Invalid files are reported when creating the pool
+        assert!(CogReaderPool::new(Bytes::from_static(b"not a tiff")).is_err());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_read_geotiff_many() {
+        use crate::io::geotiff::read_geotiff_many;
+
+        let mut files: Vec<File> = (1..=8)
+            .map(|width| {
+                let image_data: Vec<f32> = vec![0.0; 3 * width as usize];
+                let mut file = tempfile().unwrap();
+                let mut tiff = TiffEncoder::new(&mut file).unwrap();
+                tiff.write_image::<colortype::Gray32Float>(width, 3, &image_data)
+                    .unwrap();
+                file
+            })
+            .collect();
+        files.push(tempfile().unwrap()); // empty file
+
+        for max_threads in [None, Some(2)] {
+            let files: Vec<File> = files
+                .iter_mut()
+                .map(|file| {
+                    file.seek(SeekFrom::Start(0)).unwrap();
+                    file.try_clone().unwrap()
+                })
+                .collect();
+            let results = read_geotiff_many::<f32, _>(files, max_threads).unwrap();
+            assert_eq!(results.len(), 9);
+            for (index, result) in results[..8].iter().enumerate() {
+                assert_eq!(result.as_ref().unwrap().dim(), (1, 3, index + 1));
+            }
+            assert!(results[8].is_err());
+        }
+    }
+
+    #[test]
+    fn test_read_geotiff_stack() {
+        let image_tempfile = |width: u32, height: u32| -> File {
+            let image_data: Vec<f32> = (0..width * height).map(|v| v as f32).collect();
+            let mut file = tempfile().unwrap();
+            let mut tiff = TiffEncoder::new(&mut file).unwrap();
+            tiff.write_image::<colortype::Gray32Float>(width, height, &image_data)
+                .unwrap();
+            file.seek(SeekFrom::Start(0)).unwrap();
+            file
+        };
+        let files: Vec<File> = (0..3).map(|_| image_tempfile(4, 3)).collect();
+        let array = read_geotiff_stack::<f32, _>(files).unwrap();
+        assert_eq!(array.dim(), (3, 1, 3, 4));
+        assert_eq!(array[[2, 0, 2, 3]], 11.0);
+
+        // Files with a different shape cannot be stacked
+        let files: Vec<File> = vec![
+            image_tempfile(4, 3),
+            image_tempfile(4, 3),
+            image_tempfile(3, 4),
+        ];
+        let err = read_geotiff_stack::<f32, _>(files).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Shape (1, 4, 3) of file 2 does not match shape (1, 3, 4) of file 0"));
+    }
+
+    #[test]
+    fn test_validate_cog() {
+        // Tiled file with IFD before the tile data
+        let tiles = [Some(vec![0u8; 256]), Some(vec![1u8; 256])];
+        let file = tiled_tiff_tempfile(32, 16, 16, &tiles, &[0, 1]);
+        assert_eq!(validate_cog(file).unwrap(), vec![]);
+
+        // Tiles stored in reverse order
+        let file = tiled_tiff_tempfile(32, 16, 16, &tiles, &[1, 0]);
+        assert_eq!(
+            validate_cog(file).unwrap(),
+            vec![CogWarning::ChunkOffsetsNotIncreasing { ifd_index: 0 }]
+        );
+
+        // Large stripped file without overviews, and IFD written after the pixel data
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        tiff.write_image::<colortype::Gray8>(1024, 1024, &vec![0u8; 1024 * 1024])
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        assert_eq!(
+            validate_cog(file).unwrap(),
+            vec![
+                CogWarning::NotTiled { ifd_index: 0 },
+                CogWarning::IfdAfterData { ifd_index: 0 },
+                CogWarning::NoOverviews
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_gdal_metadata() {
+        let xml: &str = r#"<GDALMetadata>
+  <Item name="AREA_OR_POINT">Area</Item>
+  <Item name="COMPRESSION" domain="IMAGE_STRUCTURE">DEFLATE</Item>
+  <Item name="DESCRIPTION" sample="0" role="description">red &amp; green</Item>
+  <Item name="UNITTYPE" sample="1" role="unittype">m</Item>
+  <Item name="EMPTY" sample="1"/>
+</GDALMetadata>"#;
+        let gdal_metadata = parse_gdal_metadata(xml, 3);
+
+        assert_eq!(
+            gdal_metadata.dataset,
+            HashMap::from([(String::from("AREA_OR_POINT"), String::from("Area"))])
+        );
+        assert_eq!(gdal_metadata.bands.len(), 3);
+        assert_eq!(gdal_metadata.bands[0]["DESCRIPTION"], "red & green");
+        assert_eq!(gdal_metadata.bands[1]["UNITTYPE"], "m");
+        assert_eq!(gdal_metadata.bands[1]["EMPTY"], "");
+        assert!(gdal_metadata.bands[2].is_empty());
+    }
+
+    #[test]
+    fn test_cogreader_band_names() {
+        let xml: &str = r#"<GDALMetadata>
+  <Item name="DESCRIPTION" sample="1" role="description">nir</Item>
+</GDALMetadata>"#;
+        let mut file = tempfile().unwrap();
+        let mut tiff = TiffEncoder::new(&mut file).unwrap();
+        let mut image = tiff.new_image::<colortype::RGB8>(1, 1).unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::from_u16_exhaustive(42112), xml)
+            .unwrap();
+        image.write_data(&[0, 0, 0]).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = CogReader::new(file).unwrap();
+        assert_eq!(
+            reader.band_names().unwrap(),
+            vec![None, Some(String::from("nir")), None]
         );
     }
 }
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use bytes::Bytes;
+use reqwest::blocking::Client;
+use reqwest::header::{ACCEPT_RANGES, CONTENT_LENGTH, RANGE};
+use url::Url;
+
+/// Size of the blocks fetched with each HTTP range request, in bytes
+const BLOCK_SIZE: u64 = 64 * 1024;
+
+/// Reader over a remote file served via HTTP(S), which fetches only the byte ranges that
+/// are read (e.g. the headers and tiles of a Cloud-optimized GeoTIFF) using HTTP range
+/// requests, instead of downloading the whole file.
+///
+/// Data is fetched in blocks of 64KiB that are kept in memory once fetched, so that the
+/// many small reads made while parsing the TIFF headers do not each send a request. If
+/// the server does not support range requests (i.e. does not respond with an
+/// `Accept-Ranges: bytes` header), the whole file is downloaded up front instead.
+///
+/// Requests are sent with a blocking HTTP client, so this should not be used from within
+/// an async runtime (e.g. [`tokio`]); use [`read_geotiff_async`] there instead. Requires
+/// the `http-range` feature.
+///
+/// [`read_geotiff_async`]: crate::io::geotiff::read_geotiff_async
+///
+/// # Examples
+///
+/// ```no_run
+/// use cog3pio::io::geotiff::CogReader;
+/// use cog3pio::io::http::HttpRangeReader;
+/// use url::Url;
+///
+/// let cog_url: &str =
+///     "https://github.com/cogeotiff/rio-tiler/raw/6.4.0/tests/fixtures/cog_nodata_nan.tif";
+/// let stream = HttpRangeReader::new(Url::parse(cog_url).unwrap()).unwrap();
+/// let mut reader = CogReader::new(stream).unwrap();
+/// let array = reader.ndarray::<f32>().unwrap();
+/// ```
+pub struct HttpRangeReader {
+    /// HTTP client used to send the requests
+    client: Client,
+    /// URL of the remote file
+    url: Url,
+    /// Size of the remote file in bytes
+    length: u64,
+    /// Current position of the reader within the file
+    position: u64,
+    /// Contents of the whole file, if the server does not support range requests
+    full: Option<Bytes>,
+    /// Blocks of the file that have been fetched, keyed by block index
+    blocks: HashMap<u64, Bytes>,
+}
+
+impl HttpRangeReader {
+    /// Create a new reader over the remote file at `url`, sending a HEAD request to get the
+    /// size of the file and check whether the server supports range requests
+    pub fn new(url: Url) -> io::Result<Self> {
+        let client = Client::new();
+        let response = client
+            .head(url.clone())
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(io::Error::other)?;
+
+        let headers = response.headers();
+        let accepts_ranges: bool = headers
+            .get(ACCEPT_RANGES)
+            .is_some_and(|value| value.as_bytes() == b"bytes");
+        let content_length: Option<u64> = headers
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+
+        let mut reader = Self {
+            client,
+            url,
+            length: content_length.unwrap_or(0),
+            position: 0,
+            full: None,
+            blocks: HashMap::new(),
+        };
+
+        // Fall back to downloading the whole file if range requests are not supported
+        if !accepts_ranges || content_length.is_none() {
+            let bytes: Bytes = reader.get(None)?;
+            reader.length = bytes.len() as u64;
+            reader.full = Some(bytes);
+        }
+
+        Ok(reader)
+    }
+
+    /// Whether the data is fetched with range requests, rather than by downloading the
+    /// whole file
+    pub fn uses_ranges(&self) -> bool {
+        self.full.is_none()
+    }
+
+    /// Send a GET request for an (inclusive) range of bytes, or for the whole file
+    fn get(&self, range: Option<(u64, u64)>) -> io::Result<Bytes> {
+        let mut request = self.client.get(self.url.clone());
+        if let Some((start, end)) = range {
+            request = request.header(RANGE, format!("bytes={start}-{end}"));
+        }
+
+        request
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.bytes())
+            .map_err(io::Error::other)
+    }
+
+    /// Get a block of the file, fetching it if it has not been fetched yet
+    fn block(&mut self, block_index: u64) -> io::Result<&Bytes> {
+        if !self.blocks.contains_key(&block_index) {
+            let start: u64 = block_index * BLOCK_SIZE;
+            let end: u64 = (start + BLOCK_SIZE).min(self.length) - 1;
+            let bytes: Bytes = self.get(Some((start, end)))?;
+            if bytes.len() as u64 != end - start + 1 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "Expected {} bytes from range request, got {}",
+                        end - start + 1,
+                        bytes.len()
+                    ),
+                ));
+            }
+            self.blocks.insert(block_index, bytes);
+        }
+
+        Ok(&self.blocks[&block_index])
+    }
+}
+
+impl Read for HttpRangeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.length {
+            return Ok(0);
+        }
+
+        let position: u64 = self.position;
+        let data: &[u8] = match &self.full {
+            Some(bytes) => &bytes[position as usize..],
+            None => {
+                let block: &Bytes = self.block(position / BLOCK_SIZE)?;
+                &block[(position % BLOCK_SIZE) as usize..]
+            }
+        };
+        let num_bytes: usize = buf.len().min(data.len());
+        buf[..num_bytes].copy_from_slice(&data[..num_bytes]);
+        self.position += num_bytes as u64;
+
+        Ok(num_bytes)
+    }
+}
+
+impl Seek for HttpRangeReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position: Option<u64> = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.length.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        self.position = position.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use geo::AffineTransform;
+    use url::Url;
+
+    use crate::io::geotiff::CogReader;
+    use crate::io::http::{HttpRangeReader, BLOCK_SIZE};
+
+    /// Serve `body` over HTTP from a local port, answering HEAD requests and GET requests
+    /// for either the whole file or (if `accept_ranges`) a range of bytes, with range
+    /// responses cut short by `short_by` bytes. Returns the URL of the file, and the Range
+    /// header of each GET request received (empty when the whole file was requested).
+    fn serve(
+        body: Vec<u8>,
+        accept_ranges: bool,
+        short_by: usize,
+    ) -> (Url, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!(
+            "http://{}/file.tif",
+            listener.local_addr().unwrap()
+        ));
+        let requests: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+
+        let received = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut lines = BufReader::new(&stream).lines().map(Result::unwrap);
+                let request_line: String = lines.next().unwrap();
+                let range: Option<(usize, usize)> =
+                    lines.take_while(|line| !line.is_empty()).find_map(|line| {
+                        let (name, value) = line.split_once(": ")?;
+                        let (start, end) = name
+                            .eq_ignore_ascii_case("range")
+                            .then(|| value.strip_prefix("bytes=")?.split_once('-'))??;
+                        Some((start.parse().ok()?, end.parse().ok()?))
+                    });
+
+                let (status, data): (&str, &[u8]) = match (request_line.starts_with("HEAD"), range)
+                {
+                    (true, _) => ("200 OK", &[]),
+                    (false, Some((start, end))) => {
+                        received
+                            .lock()
+                            .unwrap()
+                            .push(format!("bytes={start}-{end}"));
+                        ("206 Partial Content", &body[start..=end - short_by])
+                    }
+                    (false, None) => {
+                        received.lock().unwrap().push(String::new());
+                        ("200 OK", &body)
+                    }
+                };
+                let content_length: usize = match request_line.starts_with("HEAD") {
+                    true => body.len(),
+                    false => data.len(),
+                };
+                let accept_ranges: &str = match accept_ranges {
+                    true => "Accept-Ranges: bytes\r\n",
+                    false => "",
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: {content_length}\r\n\
+                     {accept_ranges}Connection: close\r\n\r\n"
+                )
+                .unwrap();
+                stream.write_all(data).unwrap();
+            }
+        });
+
+        (url.unwrap(), requests)
+    }
+
+    #[test]
+    fn test_http_range_reader_blocks() {
+        let body: Vec<u8> = (0..2 * BLOCK_SIZE + 100).map(|i| (i % 251) as u8).collect();
+        let (url, requests) = serve(body.clone(), true, 0);
+        let mut stream = HttpRangeReader::new(url).unwrap();
+        assert!(stream.uses_ranges());
+
+        // Reads within a block that was already fetched do not send another request
+        let mut buf = [0u8; 10];
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, body[0..10]);
+        stream.seek(SeekFrom::Start(5)).unwrap();
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, body[5..15]);
+
+        // Last block is shorter than the block size
+        let position: u64 = stream.seek(SeekFrom::End(-20)).unwrap();
+        assert_eq!(position, 2 * BLOCK_SIZE + 80);
+        let mut tail: Vec<u8> = vec![];
+        stream.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, body[body.len() - 20..]);
+        assert_eq!(stream.read(&mut buf).unwrap(), 0);
+
+        // Reads across a block boundary fetch the next block
+        stream.seek(SeekFrom::Start(BLOCK_SIZE - 5)).unwrap();
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, body[BLOCK_SIZE as usize - 5..BLOCK_SIZE as usize + 5]);
+
+        assert!(stream
+            .seek(SeekFrom::Current(-(BLOCK_SIZE as i64) - 10))
+            .is_err());
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec!["bytes=0-65535", "bytes=131072-131171", "bytes=65536-131071"]
+        );
+    }
+
+    #[test]
+    fn test_http_range_reader_full_download() {
+        let body: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+        let (url, requests) = serve(body.clone(), false, 0);
+        let mut stream = HttpRangeReader::new(url).unwrap();
+        assert!(!stream.uses_ranges());
+
+        stream.seek(SeekFrom::Start(990)).unwrap();
+        let mut tail: Vec<u8> = vec![];
+        stream.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, body[990..]);
+
+        stream.seek(SeekFrom::Start(0)).unwrap();
+        let mut data: Vec<u8> = vec![];
+        stream.read_to_end(&mut data).unwrap();
+        assert_eq!(data, body);
+        assert_eq!(*requests.lock().unwrap(), vec![String::new()]);
+    }
+
+    #[test]
+    fn test_http_range_reader_short_range() {
+        let (url, _) = serve(vec![0; 1000], true, 1);
+        let mut stream = HttpRangeReader::new(url).unwrap();
+
+        let err: io::Error = stream.read(&mut [0u8; 10]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(
+            err.to_string(),
+            "Expected 1000 bytes from range request, got 999"
+        );
+    }
+
+    #[test]
+    fn test_http_range_reader() {
+        let cog_url: &str =
+            "https://github.com/cogeotiff/rio-tiler/raw/6.4.0/tests/fixtures/cog_nodata_nan.tif";
+        let stream = HttpRangeReader::new(Url::parse(cog_url).unwrap()).unwrap();
+        assert!(stream.uses_ranges());
+
+        let mut reader = CogReader::new(stream).unwrap();
+        assert_eq!(
+            reader.transform().unwrap(),
+            AffineTransform::new(200.0, 0.0, 499980.0, 0.0, -200.0, 5300040.0)
+        );
+        let array = reader.ndarray::<f32>().unwrap();
+        assert_eq!(array.dim(), (1, 549, 549));
+        assert_eq!(array[[0, 500, 500]], 0.13482364);
+    }
+}
@@ -1,2 +1,5 @@
 /// Read and write GeoTIFF files
 pub mod geotiff;
+/// Read remote files over HTTP using range requests
+#[cfg(feature = "http-range")]
+pub mod http;
@@ -47,7 +47,8 @@
 //! Note that the output dtype can be specified either by using a type hint
 //! (`let arr: Array3<f32>`) or via the turbofish operator (`read_geotiff::<f32>`).
 //! Currently supported dtypes include uint (u8/u16/u32/u64), int (i8/i16/i32/i64) and
-//! float (f32/f64).
+//! float (f32/f64). Half-precision float (f16) GeoTIFFs cannot be read yet, as the
+//! underlying [`tiff`] decoder does not decode 16-bit floating point samples.
 
 /// Modules for handling Input/Output of GeoTIFF data
 pub mod io;
@@ -1,13 +1,17 @@
-use std::io::Cursor;
+use std::collections::HashMap;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::time::Duration;
 
 use bytes::Bytes;
-use ndarray::Array3;
-use numpy::{PyArray1, PyArray3, ToPyArray};
-use object_store::{parse_url, ObjectStore};
-use pyo3::exceptions::{PyBufferError, PyFileNotFoundError, PyValueError};
+use ndarray::{Array2, Array3};
+use num_traits::{FromPrimitive, ToPrimitive};
+use numpy::{Element, PyArray1, PyArray3, ToPyArray};
+use object_store::{parse_url_opts, ObjectStore};
+use pyo3::exceptions::{PyBufferError, PyFileNotFoundError, PyTimeoutError, PyValueError};
 use pyo3::prelude::{pyclass, pyfunction, pymethods, pymodule, PyModule, PyResult, Python};
-use pyo3::types::PyModuleMethods;
-use pyo3::{wrap_pyfunction, Bound, PyErr};
+use pyo3::types::{PyAnyMethods, PyBytes, PyBytesMethods, PyDict, PyDictMethods, PyModuleMethods};
+use pyo3::{wrap_pyfunction, Bound, Py, PyAny, PyErr, PyRef};
+use tiff::tags::SampleFormat;
 use url::Url;
 
 use crate::io::geotiff::CogReader;
@@ -18,6 +22,13 @@ use crate::io::geotiff::CogReader;
 /// ----------
 /// path : str
 ///     The path to the file, or a url to a remote file.
+/// storage_options : dict[str, str] or None
+///     Extra options for the storage backend, e.g. credentials and region for a private
+///     S3 bucket like ``{"aws_region": "us-west-2", "aws_access_key_id": "..."}``, or
+///     ``{"aws_skip_signature": "true"}`` for anonymous access. Default is None.
+/// timeout : float or None
+///     Maximum time in seconds to wait for the file to be fetched, after which a
+///     TimeoutError is raised. Default is None (no timeout).
 ///
 /// Returns
 /// -------
@@ -37,21 +48,133 @@ use crate::io::geotiff::CogReader;
 /// >>> (1, 12, 13)
 /// >>> array.dtype
 /// >>> dtype('float32')
+///
+/// The reader can also be used as a context manager, which closes it on exit:
+///
+/// >>> with CogReader(path="...") as reader:
+/// >>>     array: np.ndarray = reader.as_numpy()
 #[pyclass]
 #[pyo3(name = "CogReader")]
 struct PyCogReader {
-    inner: CogReader<Cursor<Bytes>>,
+    inner: Option<CogReader<PyStream>>,
+}
+
+/// Byte stream that a [`PyCogReader`] decodes from
+enum PyStream {
+    /// In-memory buffer with the contents of a file read from a path or url
+    Bytes(Cursor<Bytes>),
+    /// Python file-like object with `read` and `seek` methods
+    FileObj(Py<PyAny>),
+}
+
+impl Read for PyStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            PyStream::Bytes(cursor) => cursor.read(buf),
+            PyStream::FileObj(fileobj) => Python::with_gil(|py| {
+                let data = fileobj.bind(py).call_method1("read", (buf.len(),))?;
+                let data: &[u8] = data.downcast::<PyBytes>().map_err(PyErr::from)?.as_bytes();
+                if data.len() > buf.len() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "File-like object returned {} bytes from read({}), which is more \
+                             than requested",
+                            data.len(),
+                            buf.len()
+                        ),
+                    ));
+                }
+                buf[..data.len()].copy_from_slice(data);
+                Ok(data.len())
+            }),
+        }
+    }
+}
+
+impl Seek for PyStream {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            PyStream::Bytes(cursor) => cursor.seek(pos),
+            PyStream::FileObj(fileobj) => Python::with_gil(|py| {
+                // Python's seek takes an offset and whence (0=start, 1=current, 2=end)
+                let (offset, whence): (i64, u8) = match pos {
+                    SeekFrom::Start(offset) => (offset as i64, 0),
+                    SeekFrom::Current(offset) => (offset, 1),
+                    SeekFrom::End(offset) => (offset, 2),
+                };
+                let position: u64 = fileobj
+                    .bind(py)
+                    .call_method1("seek", (offset, whence))?
+                    .extract()?;
+                Ok(position)
+            }),
+        }
+    }
 }
 
 #[pymethods]
 impl PyCogReader {
     #[new]
-    fn new(path: &str) -> PyResult<Self> {
-        let stream: Cursor<Bytes> = path_to_stream(path)?;
-        let reader =
-            CogReader::new(stream).map_err(|err| PyValueError::new_err(err.to_string()))?;
+    #[pyo3(signature = (path, storage_options = None, timeout = None))]
+    fn new(
+        path: &str,
+        storage_options: Option<HashMap<String, String>>,
+        timeout: Option<f64>,
+    ) -> PyResult<Self> {
+        let timeout: Option<Duration> = timeout
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .map_err(|err| PyValueError::new_err(format!("Invalid timeout: {err}")))?;
+        let stream: Cursor<Bytes> = path_to_stream(path, storage_options, timeout)?;
+        let reader = CogReader::new(PyStream::Bytes(stream))
+            .map_err(|err| PyValueError::new_err(err.to_string()))?;
+
+        Ok(Self {
+            inner: Some(reader),
+        })
+    }
+
+    /// Create a CogReader that decodes from a Python file-like object
+    ///
+    /// Parameters
+    /// ----------
+    /// fileobj : file-like object
+    ///     A binary file-like object with ``read`` and ``seek`` methods, e.g. an open
+    ///     file, an ``io.BytesIO`` buffer, or a file opened with fsspec.
+    ///
+    /// Returns
+    /// -------
+    /// reader : cog3pio.CogReader
+    ///     A new CogReader instance for decoding GeoTIFF files.
+    #[staticmethod]
+    fn from_fileobj(fileobj: Py<PyAny>) -> PyResult<Self> {
+        let reader = CogReader::new(PyStream::FileObj(fileobj))
+            .map_err(|err| PyValueError::new_err(err.to_string()))?;
+
+        Ok(Self {
+            inner: Some(reader),
+        })
+    }
 
-        Ok(Self { inner: reader })
+    /// Close the reader, releasing the decoder and any file contents held in memory.
+    /// Reading from a closed reader raises a ValueError.
+    fn close(&mut self) {
+        self.inner = None;
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __exit__(
+        &mut self,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc_value: &Bound<'_, PyAny>,
+        _traceback: &Bound<'_, PyAny>,
+    ) -> bool {
+        self.close();
+        false
     }
 
     /// Get image pixel data from GeoTIFF as a numpy.ndarray
@@ -62,7 +185,7 @@ impl PyCogReader {
     ///     3D array of shape (band, height, width) containing the GeoTIFF pixel data.
     fn as_numpy<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyArray3<f32>>> {
         let array_data: Array3<f32> = self
-            .inner
+            .reader()?
             .ndarray()
             .map_err(|err| PyValueError::new_err(err.to_string()))?;
 
@@ -70,6 +193,103 @@ impl PyCogReader {
         Ok(array_data.to_pyarray(py))
     }
 
+    /// Get image pixel data from GeoTIFF via the numpy array protocol, e.g. when calling
+    /// ``np.asarray(reader)``
+    ///
+    /// Parameters
+    /// ----------
+    /// dtype : np.dtype or None
+    ///     The dtype to cast the array to. Default is None, which keeps the dtype of the
+    ///     samples in the file (e.g. uint16).
+    /// copy : bool or None
+    ///     Whether to copy the data. The image is always decoded into a new array, so
+    ///     ``copy=False`` raises a ValueError. Default is None.
+    ///
+    /// Returns
+    /// -------
+    /// array : np.ndarray
+    ///     3D array of shape (band, height, width) containing the GeoTIFF pixel data.
+    #[pyo3(signature = (dtype=None, copy=None))]
+    fn __array__<'py>(
+        &mut self,
+        py: Python<'py>,
+        dtype: Option<Bound<'py, PyAny>>,
+        copy: Option<bool>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        if copy == Some(false) {
+            return Err(PyValueError::new_err(
+                "Unable to avoid copy while creating an array from a CogReader",
+            ));
+        }
+
+        let array: Bound<'py, PyAny> = self.as_numpy_native(py, None, false)?;
+        match dtype {
+            Some(dtype) => array.call_method1("astype", (dtype,)),
+            None => Ok(array),
+        }
+    }
+
+    /// Number of reduced-resolution overviews of the image
+    #[getter]
+    fn num_overviews(&mut self) -> PyResult<usize> {
+        self.reader()?
+            .num_overviews()
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    /// Get pixel data of a reduced-resolution overview as a numpy.ndarray
+    ///
+    /// Parameters
+    /// ----------
+    /// level : int
+    ///     The overview level, from 0 for the largest overview to ``num_overviews - 1``
+    ///     for the smallest one.
+    ///
+    /// Returns
+    /// -------
+    /// array : np.ndarray
+    ///     3D array of shape (band, height, width) containing the overview's pixel data,
+    ///     with the dtype of the samples in the file (e.g. uint16).
+    fn read_overview<'py>(&mut self, py: Python<'py>, level: usize) -> PyResult<Bound<'py, PyAny>> {
+        self.as_numpy_native(py, Some(level), false)
+    }
+
+    /// Get the name of each band, from the band descriptions in the GDAL metadata
+    ///
+    /// Returns
+    /// -------
+    /// band_names : list[str | None]
+    ///     Name of each band, or None for bands without a description.
+    fn band_names(&mut self) -> PyResult<Vec<Option<String>>> {
+        self.reader()?
+            .band_names()
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    /// Get the affine transformation matrix coefficients
+    ///
+    /// Returns
+    /// -------
+    /// transform : tuple[float, float, float, float, float, float]
+    ///     The (a, b, c, d, e, f) coefficients of the affine transform from image pixel
+    ///     (col, row) positions to (x, y) coordinates, in the same order as used by
+    ///     ``affine.Affine`` and ``rasterio.transform.Affine``.
+    fn transform(&mut self) -> PyResult<(f64, f64, f64, f64, f64, f64)> {
+        let transform = self
+            .reader()?
+            .transform()
+            .map_err(|err| PyValueError::new_err(err.to_string()))?;
+
+        Ok((
+            transform.a(),
+            transform.b(),
+            transform.xoff(),
+            transform.d(),
+            transform.e(),
+            transform.yoff(),
+        ))
+    }
+
     /// Get x and y coordinates as numpy.ndarray
     #[allow(clippy::type_complexity)]
     fn xy_coords<'py>(
@@ -77,16 +297,145 @@ impl PyCogReader {
         py: Python<'py>,
     ) -> PyResult<(Bound<'py, PyArray1<f64>>, Bound<'py, PyArray1<f64>>)> {
         let (x_coords, y_coords) = self
-            .inner
+            .reader()?
             .xy_coords()
             .map_err(|err| PyValueError::new_err(err.to_string()))?;
 
         Ok((x_coords.to_pyarray(py), y_coords.to_pyarray(py)))
     }
+
+    /// Convert an image pixel (row, col) position to (x, y) coordinates
+    ///
+    /// Parameters
+    /// ----------
+    /// row : float
+    ///     Row index of the pixel, where integer values refer to the upper-left corner.
+    /// col : float
+    ///     Column index of the pixel, where integer values refer to the upper-left corner.
+    ///
+    /// Returns
+    /// -------
+    /// coords : tuple[float, float]
+    ///     The (x, y) geographic/projected coordinates of the pixel position.
+    fn pixel_to_world(&mut self, row: f64, col: f64) -> PyResult<(f64, f64)> {
+        self.reader()?
+            .pixel_to_world(row, col)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    /// Convert (x, y) coordinates to an image pixel (row, col) position
+    ///
+    /// Parameters
+    /// ----------
+    /// x : float
+    ///     The x geographic/projected coordinate.
+    /// y : float
+    ///     The y geographic/projected coordinate.
+    ///
+    /// Returns
+    /// -------
+    /// pixel : tuple[float, float]
+    ///     The (row, col) position of the coordinates in image pixel space.
+    fn world_to_pixel(&mut self, x: f64, y: f64) -> PyResult<(f64, f64)> {
+        self.reader()?
+            .world_to_pixel(x, y)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
 }
 
-/// Read from a filepath or url into a byte stream
-fn path_to_stream(path: &str) -> PyResult<Cursor<Bytes>> {
+impl PyCogReader {
+    /// Get the underlying reader, or raise a ValueError if the reader has been closed
+    fn reader(&mut self) -> PyResult<&mut CogReader<PyStream>> {
+        self.inner
+            .as_mut()
+            .ok_or_else(|| PyValueError::new_err("I/O operation on closed CogReader"))
+    }
+
+    /// Decode GeoTIFF image (or one of its overviews, if `overview_level` is set) into a
+    /// numpy.ndarray with the dtype of the samples in the file (e.g. uint16), falling back
+    /// to float32 for other sample formats and bit depths. If `mask_nodata` is set, a
+    /// numpy.ma.MaskedArray of the full-resolution image is returned instead, see
+    /// [`as_numpy_typed`](Self::as_numpy_typed).
+    fn as_numpy_native<'py>(
+        &mut self,
+        py: Python<'py>,
+        overview_level: Option<usize>,
+        mask_nodata: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let sample_format: SampleFormat = self
+            .reader()?
+            .sample_format()
+            .map_err(|err| PyValueError::new_err(err.to_string()))?[0];
+        let bits_per_sample: u16 = self
+            .reader()?
+            .bits_per_sample()
+            .map_err(|err| PyValueError::new_err(err.to_string()))?[0];
+
+        match (sample_format, bits_per_sample) {
+            (SampleFormat::Uint, 8) => self.as_numpy_typed::<u8>(py, overview_level, mask_nodata),
+            (SampleFormat::Uint, 16) => self.as_numpy_typed::<u16>(py, overview_level, mask_nodata),
+            (SampleFormat::Uint, 32) => self.as_numpy_typed::<u32>(py, overview_level, mask_nodata),
+            (SampleFormat::Uint, 64) => self.as_numpy_typed::<u64>(py, overview_level, mask_nodata),
+            (SampleFormat::Int, 8) => self.as_numpy_typed::<i8>(py, overview_level, mask_nodata),
+            (SampleFormat::Int, 16) => self.as_numpy_typed::<i16>(py, overview_level, mask_nodata),
+            (SampleFormat::Int, 32) => self.as_numpy_typed::<i32>(py, overview_level, mask_nodata),
+            (SampleFormat::Int, 64) => self.as_numpy_typed::<i64>(py, overview_level, mask_nodata),
+            (SampleFormat::IEEEFP, 64) => {
+                self.as_numpy_typed::<f64>(py, overview_level, mask_nodata)
+            }
+            // Unsupported sample formats are reported by the decoder
+            _ => self.as_numpy_typed::<f32>(py, overview_level, mask_nodata),
+        }
+    }
+
+    /// Decode GeoTIFF image (or one of its overviews, if `overview_level` is set) into a
+    /// numpy.ndarray of dtype `T`.
+    ///
+    /// If `mask_nodata` is set, the full-resolution image is decoded into a
+    /// numpy.ma.MaskedArray instead, where all bands of a pixel are masked if the pixel is
+    /// invalid in the mask from [`CogReader::read_with_mask`], i.e. if every band is equal
+    /// to the NoData value or is NaN.
+    fn as_numpy_typed<'py, T: Element + FromPrimitive + ToPrimitive + Clone + 'static>(
+        &mut self,
+        py: Python<'py>,
+        overview_level: Option<usize>,
+        mask_nodata: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let reader: &mut CogReader<PyStream> = self.reader()?;
+        if mask_nodata {
+            let (array_data, valid): (Array3<T>, Array2<bool>) = reader
+                .read_with_mask()
+                .map_err(|err| PyValueError::new_err(err.to_string()))?;
+            let mask: Array3<bool> =
+                Array3::from_shape_fn(array_data.dim(), |(_, row, col)| !valid[[row, col]]);
+
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("mask", mask.to_pyarray(py))?;
+            return py.import("numpy.ma")?.call_method(
+                "masked_array",
+                (array_data.to_pyarray(py),),
+                Some(&kwargs),
+            );
+        }
+
+        let array_data: Array3<T> = match overview_level {
+            Some(level) => reader.read_overview(level),
+            None => reader.ndarray(),
+        }
+        .map_err(|err| PyValueError::new_err(err.to_string()))?;
+
+        Ok(array_data.to_pyarray(py).into_any())
+    }
+}
+
+/// Read from a filepath or url into a byte stream, with optional configuration options
+/// (e.g. credentials) passed to the object store backend, and an optional timeout for
+/// fetching the whole file
+fn path_to_stream(
+    path: &str,
+    storage_options: Option<HashMap<String, String>>,
+    timeout: Option<Duration>,
+) -> PyResult<Cursor<Bytes>> {
     // Parse URL into ObjectStore and path
     let file_or_url = match Url::from_file_path(path) {
         // Parse local filepath
@@ -95,7 +444,7 @@ fn path_to_stream(path: &str) -> PyResult<Cursor<Bytes>> {
         Err(_) => Url::parse(path)
             .map_err(|_| PyValueError::new_err(format!("Cannot parse path: {path}")))?,
     };
-    let (store, location) = parse_url(&file_or_url)
+    let (store, location) = parse_url_opts(&file_or_url, storage_options.unwrap_or_default())
         .map_err(|_| PyValueError::new_err(format!("Cannot parse url: {file_or_url}")))?;
 
     // Initialize async runtime
@@ -104,7 +453,7 @@ fn path_to_stream(path: &str) -> PyResult<Cursor<Bytes>> {
         .build()?;
 
     // Get TIFF file stream asynchronously
-    let stream = runtime.block_on(async {
+    let fetch = async {
         let result = store
             .get(&location)
             .await
@@ -114,6 +463,17 @@ fn path_to_stream(path: &str) -> PyResult<Cursor<Bytes>> {
         })?;
         // Return cursor to in-memory buffer
         Ok::<Cursor<Bytes>, PyErr>(Cursor::new(bytes))
+    };
+    let stream = runtime.block_on(async {
+        match timeout {
+            Some(duration) => tokio::time::timeout(duration, fetch).await.map_err(|_| {
+                PyTimeoutError::new_err(format!(
+                    "Timed out after {}s while fetching {path}",
+                    duration.as_secs_f64()
+                ))
+            })?,
+            None => fetch.await,
+        }
     })?;
     Ok(stream)
 }
@@ -124,11 +484,21 @@ fn path_to_stream(path: &str) -> PyResult<Cursor<Bytes>> {
 /// ----------
 /// path : str
 ///     The path to the file, or a url to a remote file.
+/// mask_nodata : bool
+///     Whether to return a masked array where pixels whose bands are all equal to the
+///     GDAL_NODATA value (or NaN) are masked across every band. Default is False.
+/// storage_options : dict[str, str] or None
+///     Extra options for the storage backend, e.g. credentials and region for a private
+///     S3 bucket. Default is None.
+/// timeout : float or None
+///     Maximum time in seconds to wait for the file to be fetched, after which a
+///     TimeoutError is raised. Default is None (no timeout).
 ///
 /// Returns
 /// -------
-/// array : np.ndarray
-///     3D array of shape (band, height, width) containing the GeoTIFF pixel data.
+/// array : np.ndarray or np.ma.MaskedArray
+///     3D array of shape (band, height, width) containing the GeoTIFF pixel data, with
+///     the same dtype (e.g. uint8, uint16, int32, float32, float64) as the GeoTIFF file.
 ///
 /// Examples
 /// --------
@@ -137,17 +507,94 @@ fn path_to_stream(path: &str) -> PyResult<Cursor<Bytes>> {
 /// array = read_geotiff("https://github.com/pka/georaster/raw/v0.1.0/data/tiff/float32.tif")
 /// assert array.shape == (20, 20)
 #[pyfunction]
-#[pyo3(name = "read_geotiff")]
-fn read_geotiff_py<'py>(path: &str, py: Python<'py>) -> PyResult<Bound<'py, PyArray3<f32>>> {
+#[pyo3(
+    name = "read_geotiff",
+    signature = (path, mask_nodata = false, storage_options = None, timeout = None)
+)]
+fn read_geotiff_py<'py>(
+    path: &str,
+    mask_nodata: bool,
+    storage_options: Option<HashMap<String, String>>,
+    timeout: Option<f64>,
+    py: Python<'py>,
+) -> PyResult<Bound<'py, PyAny>> {
     // Open URL with TIFF decoder
-    let mut reader = PyCogReader::new(path)?;
+    let mut reader = PyCogReader::new(path, storage_options, timeout)?;
 
-    // Decode TIFF into numpy ndarray
-    let array_data = reader.as_numpy(py)?;
+    // Decode TIFF into numpy ndarray, or a masked array with NaN and NoData pixels masked
+    let array_data: Bound<PyAny> = reader.as_numpy_native(py, None, mask_nodata)?;
 
     Ok(array_data)
 }
 
+/// Read a GeoTIFF file from a path on disk or a url into an ndarray along with its
+/// coordinates, affine transform and coordinate reference system
+///
+/// The file is only fetched once, and the result can be passed directly to
+/// ``xarray.DataArray(data, coords={"y": y, "x": x}, dims=("band", "y", "x"))``.
+///
+/// Parameters
+/// ----------
+/// path : str
+///     The path to the file, or a url to a remote file.
+/// storage_options : dict[str, str] or None
+///     Extra options for the storage backend, e.g. credentials and region for a private
+///     S3 bucket. Default is None.
+/// timeout : float or None
+///     Maximum time in seconds to wait for the file to be fetched, after which a
+///     TimeoutError is raised. Default is None (no timeout).
+///
+/// Returns
+/// -------
+/// dataarray : dict
+///     Dictionary with the keys:
+///
+///     - ``data`` : 3D np.ndarray of shape (band, height, width) containing the GeoTIFF
+///       pixel data, with the same dtype as the GeoTIFF file
+///     - ``x`` : 1D np.ndarray of the x coordinates of the pixel centers
+///     - ``y`` : 1D np.ndarray of the y coordinates of the pixel centers
+///     - ``transform`` : tuple of the (a, b, c, d, e, f) affine transform coefficients
+///     - ``crs`` : the coordinate reference system as an "EPSG:<code>" string, or None if
+///       it has no EPSG code
+///
+/// Examples
+/// --------
+/// import xarray as xr
+/// from cog3pio import read_dataarray
+///
+/// result = read_dataarray("https://github.com/pka/georaster/raw/v0.1.0/data/tiff/float32.tif")
+/// dataarray = xr.DataArray(
+///     data=result["data"],
+///     coords={"y": result["y"], "x": result["x"]},
+///     dims=("band", "y", "x"),
+/// )
+#[pyfunction]
+#[pyo3(signature = (path, storage_options = None, timeout = None))]
+fn read_dataarray<'py>(
+    path: &str,
+    storage_options: Option<HashMap<String, String>>,
+    timeout: Option<f64>,
+    py: Python<'py>,
+) -> PyResult<Bound<'py, PyDict>> {
+    // Open URL with TIFF decoder
+    let mut reader = PyCogReader::new(path, storage_options, timeout)?;
+
+    let (x_coords, y_coords) = reader.xy_coords(py)?;
+    let epsg_code: Option<u32> = reader
+        .reader()?
+        .epsg_code()
+        .map_err(|err| PyValueError::new_err(err.to_string()))?;
+
+    let dataarray = PyDict::new(py);
+    dataarray.set_item("data", reader.as_numpy_native(py, None, false)?)?;
+    dataarray.set_item("x", x_coords)?;
+    dataarray.set_item("y", y_coords)?;
+    dataarray.set_item("transform", reader.transform()?)?;
+    dataarray.set_item("crs", epsg_code.map(|code| format!("EPSG:{code}")))?;
+
+    Ok(dataarray)
+}
+
 /// A Python module implemented in Rust. The name of this function must match
 /// the `lib.name` setting in the `Cargo.toml`, else Python will not be able to
 /// import the module.
@@ -157,5 +604,6 @@ fn cog3pio(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyCogReader>()?;
     // Register Python functions
     m.add_function(wrap_pyfunction!(read_geotiff_py, m)?)?;
+    m.add_function(wrap_pyfunction!(read_dataarray, m)?)?;
     Ok(())
 }